@@ -3,4 +3,46 @@ pub static CHUNK_SIZE_Y: u8 = 32;
 pub static CHUNK_SIZE_Z: u8 = 32;
 pub static CHUNK_LENGTH: usize = CHUNK_SIZE_X as usize * CHUNK_SIZE_Y as usize * CHUNK_SIZE_Z as usize;
 
-pub static MODDING: bool = true;
\ No newline at end of file
+pub static MODDING: bool = true;
+
+// worlds are saved in this folder next to the executable
+pub static SAVES_DIRECTORY: &str = "saves";
+
+// chunk streaming, all distances are in chunks
+pub static VIEW_DISTANCE: i64 = 6;
+pub static VERTICAL_VIEW_DISTANCE: i64 = 4;
+// chunks are only unloaded once they are this much further than the view distance
+pub static CHUNK_UNLOAD_MARGIN: i64 = 2;
+// how many chunks may generate at the same time
+pub static CHUNK_JOBS: usize = 8;
+// how many chunks are loaded or handed to the generator per frame
+pub static CHUNK_LOADS_PER_UPDATE: usize = 4;
+// how many edits every player can undo
+pub static EDIT_HISTORY_LENGTH: usize = 100;
+// the most blocks a single world edit command may change, a hundred cubed
+pub static EDIT_VOLUME_LIMIT: u64 = 1_000_000;
+
+// world simulation steps, fluids and other block updates count in ticks
+pub static TICKS_PER_SECOND: u32 = 20;
+// ticks in a day and night, twenty minutes
+pub static DAY_LENGTH: u64 = 24000;
+// fluid blocks that flow per tick at most, the rest wait for the next one
+pub static FLUID_UPDATES_PER_TICK: usize = 1000;
+// scheduled block updates that run per tick at most, like fluids
+pub static BLOCK_UPDATES_PER_TICK: usize = 1000;
+// blocks picked in every loaded chunk per tick for things like grass spreading,
+// so every block is picked about once a minute
+pub static RANDOM_TICKS_PER_CHUNK: usize = 24;
+// oceans and lakes are filled with water up to this height
+pub static SEA_LEVEL: i64 = 8;
+// columns mix the biomes closest to their climate, a biome fades out once
+// it is this much further in temperature and moisture than the closest one
+pub static BIOME_BLEND_COUNT: usize = 4;
+pub static BIOME_BLEND_DISTANCE: f32 = 6.0;
+// the weather of every area this many blocks wide changes at most once per
+// weather length, and takes the transition to change
+pub static WEATHER_CELL_SIZE: i64 = 512;
+pub static WEATHER_LENGTH: u64 = 12000;
+pub static WEATHER_TRANSITION: u64 = 600;
+// columns picked in every loaded column of chunks per tick for snow to settle on
+pub static SNOW_TICKS_PER_COLUMN: usize = 1;
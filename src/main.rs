@@ -1,11 +1,18 @@
 pub mod renderer;
 pub mod world;
+pub mod physics;
 pub mod config;
 
 use world::object::Object;
+use config::SAVES_DIRECTORY;
 
 fn main() {
-    let mut world = world::world::World::new();
+    let exe_path = std::env::current_exe().expect("Failed to get current executable path");
+    let save_directory = exe_path.parent().expect("Failed to get executable directory").join(SAVES_DIRECTORY).join("world");
+    // a seed can be given with --seed, it only matters when the world is created
+    let args: Vec<String> = std::env::args().collect();
+    let seed = args.iter().position(|arg| arg == "--seed").and_then(|index| args.get(index + 1));
+    let mut world = world::world::World::open(&save_directory, seed.map(|seed| seed.as_str())).expect("Failed to open the world save");
 
     let cube_object1 = world::objects::cube::create_cube((1.0, 1.0, 4.0), (0.0, 0.0, 0.0));
     world.add_object(Object::create(
@@ -1 +1,25 @@
-mod physics;
\ No newline at end of file
+#[allow(clippy::module_inception)]
+pub mod physics;
+
+use cgmath::Point3;
+
+// the player the physics moves around, in the same units of half a block as the camera
+#[derive(Clone)]
+pub struct GameData {
+    pub camera_position: Point3<f32>,
+    pub camera_acceleration: Point3<f32>,
+    pub camera_acceleration_walking: Point3<f32>,
+    pub grounded: bool,
+    pub jumping: bool
+}
+impl GameData {
+    pub fn new(camera_position: (f32, f32, f32)) -> Self {
+        Self {
+            camera_position: camera_position.into(),
+            camera_acceleration: (0.0, 0.0, 0.0).into(),
+            camera_acceleration_walking: (0.0, 0.0, 0.0).into(),
+            grounded: false,
+            jumping: false
+        }
+    }
+}
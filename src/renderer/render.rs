@@ -1,13 +1,17 @@
 use cgmath::{Matrix4, SquareMatrix, Matrix};
 use winit::window::Window;
+use std::collections::HashMap;
 use std::iter;
 use wgpu::BindGroup;
+use wgpu::util::DeviceExt;
 use rust_embed::RustEmbed;
 use image::GenericImageView;
 
 use crate::renderer::transforms;
 use crate::renderer::vertex::Vertex;
 use crate::world::world::World;
+use crate::world::chunk_map::ChunkPosition;
+use crate::world::time::Sky;
 
 #[derive(RustEmbed)]
 #[folder = "assets/"]
@@ -23,6 +27,13 @@ pub struct Renderer {
     uniform_bind_group: Vec<wgpu::BindGroup>,
     num_vertices: Vec<u32>,
 
+    // chunk meshes all share the world texture, so they share one bind group
+    chunk_bind_group: wgpu::BindGroup,
+    chunk_meshes: HashMap<ChunkPosition, (wgpu::Buffer, u32)>,
+    // blocks that move, like falling sand, rebuilt whenever they do
+    entity_mesh: Option<(wgpu::Buffer, u32)>,
+    sky_color: [f32; 3],
+
     uniform_bind_group_layout: wgpu::BindGroupLayout,
     vertex_uniform_buffer: wgpu::Buffer,
     fragment_uniform_buffer: wgpu::Buffer,
@@ -45,6 +56,29 @@ impl Renderer {
         vertex_uniform_buffer: &wgpu::Buffer, fragment_uniform_buffer: &wgpu::Buffer,
         texture: &wgpu::Texture, texture_size: wgpu::Extent3d, rgba: &Vec<u8>, width: u32, height: u32
     ) -> (BindGroup, wgpu::Buffer) {
+        let uniform_bind_group = Self::create_bind_group(
+            init, uniform_bind_group_layout, 
+            vertex_uniform_buffer, fragment_uniform_buffer, 
+            texture, texture_size, rgba, width, height
+        );
+
+        let max_buffer_size = 1024 * 1024 * 6; // 8MB buffer
+        let vertex_buffer = init.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Vertex Buffer"),
+            size: max_buffer_size as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+
+        (uniform_bind_group, vertex_buffer)
+    }
+
+    fn create_bind_group(
+        init: &transforms::InitWgpu, 
+        uniform_bind_group_layout: &wgpu::BindGroupLayout, 
+        vertex_uniform_buffer: &wgpu::Buffer, fragment_uniform_buffer: &wgpu::Buffer,
+        texture: &wgpu::Texture, texture_size: wgpu::Extent3d, rgba: &Vec<u8>, width: u32, height: u32
+    ) -> BindGroup {
         init.queue.write_texture(
             wgpu::ImageCopyTexture {
                 texture: &texture,
@@ -95,15 +129,7 @@ impl Renderer {
             label: Some("Uniform Bind Group"),
         });
 
-        let max_buffer_size = 1024 * 1024 * 6; // 8MB buffer
-        let vertex_buffer = init.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Vertex Buffer"),
-            size: max_buffer_size as u64,
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false
-        });
-
-        return (uniform_bind_group, vertex_buffer)
+        uniform_bind_group
     }
 
     pub async fn new(window: &Window) -> Self {
@@ -215,7 +241,7 @@ impl Renderer {
 
         let fragment_uniform_buffer = init.device.create_buffer(&wgpu::BufferDescriptor{
             label: Some("Fragment Uniform Buffer"),
-            size: 32,
+            size: 80,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -259,6 +285,14 @@ impl Renderer {
         let uniform_bind_group = Vec::new();
         let num_vertices = Vec::new();
 
+        let chunk_bind_group = Self::create_bind_group(
+            &init, &uniform_bind_group_layout, 
+            &vertex_uniform_buffer, &fragment_uniform_buffer,
+            &world_texture, world_texture_size, &world_texture_rgba, 
+            world_texture_width, world_texture_height
+        );
+        let chunk_meshes = HashMap::new();
+
         let frame = 0;
 
         Self {
@@ -271,6 +305,11 @@ impl Renderer {
             uniform_bind_group,
             num_vertices,
 
+            chunk_bind_group,
+            chunk_meshes,
+            entity_mesh: None,
+            sky_color: [0.2, 0.247, 0.314],
+
             uniform_bind_group_layout,
             vertex_uniform_buffer,
             fragment_uniform_buffer,
@@ -325,6 +364,25 @@ impl Renderer {
         self.frame += 1;
     }
 
+    // the sky colour is also what the screen is cleared to
+    pub fn set_sky(&mut self, sky: &Sky) {
+        self.sky_color = sky.sky_color;
+        let light_direction = [sky.light_direction[0], sky.light_direction[1], sky.light_direction[2], 0.0];
+        let sky_color = [sky.sky_color[0], sky.sky_color[1], sky.sky_color[2], 1.0];
+        let ambient = [sky.sky_brightness, sky.ambient_light, sky.fog_distance, 0.0];
+        self.init.queue.write_buffer(&self.fragment_uniform_buffer, 32, bytemuck::cast_slice(&light_direction));
+        self.init.queue.write_buffer(&self.fragment_uniform_buffer, 48, bytemuck::cast_slice(&sky_color));
+        self.init.queue.write_buffer(&self.fragment_uniform_buffer, 64, bytemuck::cast_slice(&ambient));
+    }
+
+    pub fn get_camera_position(&self) -> (f32, f32, f32) {
+        self.camera_position
+    }
+
+    pub fn set_camera_position(&mut self, camera_position: (f32, f32, f32)) {
+        self.camera_position = camera_position;
+    }
+
     // replace all objects in the world
     pub fn set_objects(&mut self, world: &World) {
         let objects = world.get_objects();
@@ -352,6 +410,44 @@ impl Renderer {
         }
     }
 
+    // replaces the mesh of a chunk, an empty mesh removes it
+    pub fn set_chunk_mesh(&mut self, position: ChunkPosition, vertices: &[Vertex]) {
+        if vertices.is_empty() {
+            self.remove_chunk_mesh(position);
+            return;
+        }
+
+        let vertex_buffer = self.init.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Chunk Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.chunk_meshes.insert(position, (vertex_buffer, vertices.len() as u32));
+    }
+
+    // an empty mesh removes it
+    pub fn set_entity_mesh(&mut self, vertices: &[Vertex]) {
+        if vertices.is_empty() {
+            self.entity_mesh = None;
+            return;
+        }
+
+        let vertex_buffer = self.init.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Entity Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.entity_mesh = Some((vertex_buffer, vertices.len() as u32));
+    }
+
+    pub fn has_entity_mesh(&self) -> bool {
+        self.entity_mesh.is_some()
+    }
+
+    pub fn remove_chunk_mesh(&mut self, position: ChunkPosition) {
+        self.chunk_meshes.remove(&position);
+    }
+
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
         //let output = self.init.surface.get_current_frame()?.output;
         let output = self.init.surface.get_current_texture()?;
@@ -389,9 +485,9 @@ impl Renderer {
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.2,
-                            g: 0.247,
-                            b: 0.314,
+                            r: self.sky_color[0] as f64,
+                            g: self.sky_color[1] as f64,
+                            b: self.sky_color[2] as f64,
                             a: 1.0,
                         }),
                         store: true,
@@ -414,6 +510,16 @@ impl Renderer {
                 render_pass.set_bind_group(0, &self.uniform_bind_group[i], &[]);
                 render_pass.draw(0..self.num_vertices[i], 0..1);
             }
+
+            render_pass.set_bind_group(0, &self.chunk_bind_group, &[]);
+            for (vertex_buffer, num_vertices) in self.chunk_meshes.values() {
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.draw(0..*num_vertices, 0..1);
+            }
+            if let Some((vertex_buffer, num_vertices)) = &self.entity_mesh {
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.draw(0..*num_vertices, 0..1);
+            }
         }
 
         self.init.queue.submit(iter::once(encoder.finish()));
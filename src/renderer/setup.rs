@@ -1,8 +1,16 @@
 use winit::{event::*, event_loop::{ControlFlow, EventLoop}};
 use crate::{renderer::render::Renderer, world::world::World};
+use crate::world::streaming::ChunkStreamer;
+use crate::world::objects::chunk_mesh::{render_chunk, render_falling_blocks};
+use crate::renderer::vertex::create_lit_vertices;
+use crate::world::chunk_map::ChunkMap;
+use crate::world::commands::{execute_command, spawn_console, CommandContext};
+use crate::world::edit_journal::LOCAL_PLAYER;
+use crate::physics::{self, GameData};
+use crate::config::{TICKS_PER_SECOND, VIEW_DISTANCE};
 
 // this will call the render class
-pub fn start_engine(world: World) {
+pub fn start_engine(mut world: World) {
     env_logger::init();
     let event_loop = EventLoop::new();
     let window = winit::window::WindowBuilder::new().build(&event_loop).unwrap();
@@ -15,9 +23,18 @@ pub fn start_engine(world: World) {
 
     let mut renderer = pollster::block_on(Renderer::new(&window));    
     let render_start_time = std::time::Instant::now();
+    let tick_length = std::time::Duration::from_secs_f64(1.0 / TICKS_PER_SECOND as f64);
+    let mut last_tick = render_start_time;
+    let mut last_frame = render_start_time;
+
+    let mut chunk_streamer = ChunkStreamer::new(&world, VIEW_DISTANCE);
+    let console = spawn_console();
+    let mut game_data = GameData::new(renderer.get_camera_position());
+
+    // the objects never change, so they only have to be uploaded once
+    renderer.set_objects(&world);
 
     //let mut mouse_locked = true;
-    let mut frame = 0;
 
     event_loop.run(move |event, _, control_flow| {
         match event {
@@ -35,7 +52,12 @@ pub fn start_engine(world: World) {
                             window.set_cursor_visible(true);
                         }
                     }
-                    WindowEvent::CloseRequested {} => *control_flow = ControlFlow::Exit,
+                    WindowEvent::CloseRequested {} => {
+                        if let Err(err) = world.save() {
+                            eprintln!("Failed to save the world: {:?}", err);
+                        }
+                        *control_flow = ControlFlow::Exit
+                    }
                     WindowEvent::Resized(physical_size) => {
                         renderer.resize(*physical_size);
                     }
@@ -49,11 +71,50 @@ pub fn start_engine(world: World) {
                 let now = std::time::Instant::now();
                 let dt = now - render_start_time;
 
-                renderer.update(dt);
+                // the physics counts in twentieths of a second
+                let frame_time = (now - last_frame).as_secs_f32() * 20.0;
+                last_frame = now;
+                physics::physics::update(&mut game_data, &world, frame_time);
+                renderer.set_camera_position(game_data.camera_position.into());
+
+                // the renderer works in units of half a block
+                let camera_position = renderer.get_camera_position();
+                let player_position = (camera_position.0 / 2.0, camera_position.1 / 2.0, camera_position.2 / 2.0);
+                chunk_streamer.update(&mut world, player_position);
+
+                // the world runs at a fixed tick rate however fast the frames are,
+                // after a long stall it just carries on instead of catching up
+                if now - last_tick > tick_length * TICKS_PER_SECOND {
+                    last_tick = now - tick_length;
+                }
+                while now - last_tick >= tick_length {
+                    world.tick();
+                    last_tick += tick_length;
+                }
+
+                while let Ok(command) = console.try_recv() {
+                    let context = CommandContext {
+                        player: LOCAL_PLAYER,
+                        position: ChunkMap::block_at_point(player_position.0, player_position.1, player_position.2)
+                    };
+                    match execute_command(&mut world, &context, &command) {
+                        Ok(message) => println!("{}", message),
+                        Err(err) => eprintln!("{}", err)
+                    }
+                }
 
-                if frame % 120 == 0 {
-                    renderer.set_objects(&world);
+                for position in world.get_chunks_mut().take_changed() {
+                    let (vertices, normals, colors, uvs, sky) = render_chunk(world.get_chunks(), world.get_blocks(), position);
+                    renderer.set_chunk_mesh(position, &create_lit_vertices(vertices, normals, colors, uvs, sky));
                 }
+                if !world.get_falling_blocks().is_empty() || renderer.has_entity_mesh() {
+                    let (vertices, normals, colors, uvs, sky) = render_falling_blocks(world.get_chunks(), world.get_blocks(), world.get_falling_blocks());
+                    renderer.set_entity_mesh(&create_lit_vertices(vertices, normals, colors, uvs, sky));
+                }
+                let eye = ChunkMap::block_at_point(player_position.0, player_position.1, player_position.2);
+                renderer.set_sky(&world.sky_at(eye));
+
+                renderer.update(dt);
 
                 match renderer.render() {
                     Ok(_) => {}
@@ -61,8 +122,6 @@ pub fn start_engine(world: World) {
                     Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
                     Err(e) => eprintln!("{:?}", e),
                 }
-
-                frame += 1;
             }
             Event::MainEventsCleared => {
                 window.request_redraw();
@@ -39,4 +39,15 @@ pub fn create_vertices(vertices: Vec<[f64; 3]>, normals: Vec<[i8; 3]>, colors: V
         vertex_list.push(vertex(vertices[i], normals[i], colors[i], uvs[i]));
     }
     return vertex_list.to_vec()
-}
\ No newline at end of file
+}
+
+// like create_vertices, with the sky light level and ambient occlusion of
+// every vertex in the last two uv components for the shader
+pub fn create_lit_vertices(vertices: Vec<[f64; 3]>, normals: Vec<[i8; 3]>, colors: Vec<[f32; 3]>, uvs: Vec<[f32; 2]>, sky: Vec<[f32; 2]>) -> Vec<Vertex> {
+    let mut vertex_list = create_vertices(vertices, normals, colors, uvs);
+    for (vertex, sky) in vertex_list.iter_mut().zip(sky) {
+        vertex.uv[2] = sky[0];
+        vertex.uv[3] = sky[1];
+    }
+    vertex_list
+}
@@ -29,6 +29,12 @@ fn vs_main(@location(0) pos: vec4<f32>, @location(1) normal: vec4<f32>, @locatio
 struct FragUniforms {
     light_position : vec4<f32>,
     eye_position : vec4<f32>,
+    // towards the sun by day and the moon by night
+    light_direction : vec4<f32>,
+    sky_color : vec4<f32>,
+    // x is how bright sky light is right now, y how much of it reaches faces turned away from the light,
+    // z how far the fog starts compared to a clear day
+    ambient : vec4<f32>,
 };
 @binding(1) @group(0) var<uniform> frag_uniforms : FragUniforms;
 
@@ -44,22 +50,34 @@ struct LightUniforms {
 @binding(2) @group(0) var texture: texture_2d<f32>;
 @binding(3) @group(0) var texture_sampler: sampler;
 
+// the same curve chunk_mesh.rs uses for block light
+const MIN_BRIGHTNESS: f32 = 0.05;
+fn brightness(level: f32) -> f32 {
+    return MIN_BRIGHTNESS + (1.0 - MIN_BRIGHTNESS) * pow(level, 1.5);
+}
+
 @fragment
 fn fs_main(@location(0) v_position: vec4<f32>, @location(1) v_normal: vec4<f32>, @location(2) v_color: vec4<f32>, @location(3) v_uv: vec4<f32>) ->  @location(0) vec4<f32> {
     let N:vec3<f32> = normalize(v_normal.xyz);
     //let L:vec3<f32> = normalize(frag_uniforms.light_position.xyz - v_position.xyz);
-    let L:vec3<f32> = normalize(vec3(0.5, 1.0, 0.5));
+    let L:vec3<f32> = normalize(frag_uniforms.light_direction.xyz);
     let V:vec3<f32> = normalize(frag_uniforms.eye_position.xyz - v_position.xyz);
     let H:vec3<f32> = normalize(L + V);
     let texture_color: vec4<f32> = textureSample(texture, texture_sampler, v_uv.xy);
 
-    let fog_color: vec3<f32> = vec3(0.2, 0.247, 0.314);
-    let fog_start: f32 = 150.0;
-    let fog_end: f32 = 200.0;
+    let fog_color: vec3<f32> = frag_uniforms.sky_color.rgb;
+    let fog_start: f32 = 150.0 * frag_uniforms.ambient.z;
+    let fog_end: f32 = 200.0 * frag_uniforms.ambient.z;
     let distance: f32 = length(v_position.xyz - frag_uniforms.eye_position.xyz);
     let fog_factor: f32 = clamp((fog_end - distance) / (fog_end - fog_start), 0.0, 1.0);
 
-    let rgb_effect: vec3<f32> = texture_color.rgb * v_color.rgb;
+    // chunk vertices carry their sky light level and ambient occlusion in uv.zw,
+    // everything else has 0 there and only uses its colour
+    let facing: f32 = mix(frag_uniforms.ambient.y, 1.0, max(dot(N, L), 0.0));
+    let sky_light: f32 = brightness(v_uv.z * frag_uniforms.ambient.x) * v_uv.w * facing;
+    let light: vec3<f32> = max(v_color.rgb, vec3(sky_light));
+
+    let rgb_effect: vec3<f32> = texture_color.rgb * light;
     let alpha:f32 = texture_color.a * v_color.a;
     let final_color: vec4<f32> = vec4(rgb_effect, alpha);
 
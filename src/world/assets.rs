@@ -1,55 +1,136 @@
 use rust_embed::RustEmbed;
 use std::path::PathBuf;
 use std::fs;
-use std::io::Read;
+use std::io::{self, Read};
 
 use crate::config::MODDING;
-use crate::world::biomes::Biomes;
+use crate::world::biomes::{Biome, Biomes};
+use crate::world::block_states::state_bits;
+use crate::world::blocks::{BlockDef, BlockRegistry, ShapeDef};
+use crate::world::structures::{Structure, StructureDef, Structures};
 
 #[derive(RustEmbed)]
 #[folder = "assets/"]
 struct Assets;
 
-pub fn load_biomes(biomes: &Biomes) {
+// finds every json file in an asset folder and returns (file name, content),
+// with modding enabled files in the assets folder next to the executable are
+// loaded alongside the ones built into the game
+fn load_json_files(folder: &str) -> Vec<(String, String)> {
     let mut json_files = Vec::new();
     let mut exe_dir: PathBuf = PathBuf::new();
     if MODDING {
         let exe_path = std::env::current_exe().expect("Failed to get current executable path");
         exe_dir = exe_path.parent().expect("Failed to get executable directory").to_path_buf();
-        let models_dir = exe_dir.join("assets/biomes");
+        let models_dir = exe_dir.join("assets").join(folder);
         if models_dir.exists() && models_dir.is_dir() {
-            println!("Found the modded directory for biomes");
-            for entry in fs::read_dir(&models_dir).expect("Failed to read models directory") {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if path.extension().map_or(false, |ext| ext == "json") {
-                        if let Some(file_name) = path.strip_prefix(&exe_dir).ok().and_then(|p| p.to_str()) {
-                            println!("Found the modded biome file: {}", file_name);
-                            json_files.push(file_name.to_string());
-                        }
+            println!("Found the modded directory for {}", folder);
+            for entry in fs::read_dir(&models_dir).expect("Failed to read models directory").flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    if let Some(file_name) = path.strip_prefix(&exe_dir).ok().and_then(|p| p.to_str()) {
+                        println!("Found the modded file: {}", file_name);
+                        json_files.push(file_name.to_string());
                     }
                 }
             }
         }
     }
+    let prefix = format!("{}/", folder);
     json_files.extend(
         Assets::iter()
-            .filter(|file| file.starts_with("biomes/") && file.ends_with(".json"))
+            .filter(|file| file.starts_with(&prefix) && file.ends_with(".json"))
             .map(|file| file.to_string())
     );
     json_files.sort();
     json_files.dedup();
+
+    let mut contents = Vec::new();
     for file in json_files {
         println!("Found JSON file: {}", file);
         let file_path = exe_dir.join(&file);
         if file_path.exists() {
             let mut file_content = String::new();
-            let mut file = fs::File::open(&file_path).expect("Failed to open file");
-            file.read_to_string(&mut file_content).expect("Failed to read file");
-            //handle_biome_data(&mut world_data, &file_content);
+            let mut opened_file = fs::File::open(&file_path).expect("Failed to open file");
+            opened_file.read_to_string(&mut file_content).expect("Failed to read file");
+            contents.push((file, file_content));
         } else if let Some(asset) = Assets::get(&file) {
             let json_content = std::str::from_utf8(asset.data.as_ref()).expect("Invalid UTF-8");
-            //handle_biome_data(&mut world_data, json_content);
+            contents.push((file, json_content.to_string()));
+        }
+    }
+    contents
+}
+
+// biomes need the blocks and structures to be loaded first
+pub fn load_biomes(biomes: &mut Biomes, blocks: &BlockRegistry, structures: &Structures) {
+    for (file, json_content) in load_json_files("biomes") {
+        let biome = Biome::from_json(&json_content).and_then(|biome| biome.check_blocks(blocks).map(|_| biome));
+        match biome {
+            Ok(biome) => {
+                for warning in biome.missing_structures(structures) {
+                    eprintln!("The biome {} uses {}", file, warning);
+                }
+                biomes.add(biome);
+            }
+            Err(err) => eprintln!("Failed to load the biome {}: {}", file, err)
+        }
+    }
+}
+
+pub fn load_shapes(blocks: &mut BlockRegistry) {
+    for (file, json_content) in load_json_files("models/shapes") {
+        match serde_json::from_str::<ShapeDef>(&json_content) {
+            Ok(shape) => blocks.add_shape(shape),
+            Err(err) => eprintln!("Failed to load the shape {}: {}", file, err)
+        }
+    }
+}
+
+pub fn load_blocks(blocks: &mut BlockRegistry) {
+    for (file, json_content) in load_json_files("models/blocks") {
+        match serde_json::from_str::<BlockDef>(&json_content) {
+            Ok(block) => {
+                if blocks.get_shape(&block.shape).is_none() {
+                    eprintln!("The block {} uses the unknown shape {}", file, block.shape);
+                }
+                // states are packed into 16 bits in the chunks
+                if state_bits(&block.states) > 16 {
+                    eprintln!("The block {} has too many states", file);
+                    continue;
+                }
+                blocks.register(block);
+            }
+            Err(err) => eprintln!("Failed to load the block {}: {}", file, err)
         }
     }
-}
\ No newline at end of file
+}
+
+// structures need the blocks to be loaded first
+pub fn load_structures(structures: &mut Structures, blocks: &BlockRegistry) {
+    for (file, json_content) in load_json_files("structures") {
+        let structure = serde_json::from_str::<StructureDef>(&json_content)
+            .map_err(|err| err.to_string())
+            .and_then(|structure| Structure::from_def(&structure, blocks));
+        match structure {
+            Ok(structure) => structures.add(structure),
+            Err(err) => eprintln!("Failed to load the structure {}: {}", file, err)
+        }
+    }
+}
+
+// writes into the assets folder next to the executable, with modding enabled
+// the file is loaded again on the next start
+pub fn save_json_file(folder: &str, file_name: &str, content: &str) -> io::Result<PathBuf> {
+    let exe_path = std::env::current_exe()?;
+    let directory = exe_path.parent().map(|parent| parent.join("assets").join(folder))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Failed to get executable directory"))?;
+    // a file name with separators or .. could write anywhere
+    if file_name.contains(['/', '\\']) || file_name.contains("..") {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid file name {}", file_name)));
+    }
+    fs::create_dir_all(&directory)?;
+    let path = directory.join(file_name);
+    fs::write(&path, content)?;
+    Ok(path)
+}
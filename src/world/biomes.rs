@@ -1,14 +1,108 @@
+use serde::Deserialize;
+
+use crate::world::blocks::BlockRegistry;
+use crate::world::generation::climate::Climate;
+use crate::world::generation::terrain::TerrainProfile;
+use crate::world::structures::Structures;
+
+// the blocks of one layer under the surface, one of them is picked for every
+// block, and how deep below the surface the layer starts. every layer goes
+// down to the next one
+pub type BlockLevel = (Vec<String>, u32);
+
+// a biome file from assets/biomes, the world picks the biome whose
+// temperature and moisture are closest to the climate of a column
+#[derive(Deserialize, Clone, Debug)]
 pub struct Biome {
-    name: String,
-    temperature: f32,
-    moisture: f32,
-    height: u32,
-    sea_level: u32
+    #[serde(rename = "biome_name")]
+    pub name: String,
+    pub temperature: f32,
+    pub moisture: f32,
+    // how far above the sea the ground lies on average, unless the terrain says otherwise
+    pub height: u32,
+    #[serde(default)]
+    pub terrain: TerrainProfile,
+    pub block_levels: Vec<BlockLevel>,
+    // -1 for the sea level of the world
+    pub sea_level: i64,
+    // structure names with the chance one grows on a surface block
+    #[serde(default)]
+    pub trees: Vec<(String, f32)>,
+    #[serde(default)]
+    pub folliage: Vec<(String, f32)>,
+    #[serde(default)]
+    pub buildings: Vec<(String, f32)>
 }
 impl Biome {
+    // the errors name the field that is wrong, like "block_levels[1][0]: invalid type"
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let deserializer = &mut serde_json::Deserializer::from_str(json);
+        let biome: Self = serde_path_to_error::deserialize(deserializer).map_err(|err| {
+            let path = err.path().to_string();
+            match path.as_str() {
+                "." | "?" => err.into_inner().to_string(),
+                _ => format!("{}: {}", path, err.into_inner())
+            }
+        })?;
+        biome.validate()?;
+        Ok(biome)
+    }
+
+    // what serde can't check on its own
+    fn validate(&self) -> Result<(), String> {
+        if self.block_levels.is_empty() {
+            return Err("block_levels: a biome needs at least one layer".to_string());
+        }
+        for (index, (names, _)) in self.block_levels.iter().enumerate() {
+            if names.is_empty() {
+                return Err(format!("block_levels[{}]: the layer has no blocks", index));
+            }
+        }
+        if !(0.0..=100.0).contains(&self.moisture) {
+            return Err(format!("moisture: {} is not between 0 and 100", self.moisture));
+        }
+        self.terrain.validate()?;
+        if self.sea_level < -1 {
+            return Err(format!("sea_level: {} is below -1", self.sea_level));
+        }
+        for (field, list) in [("trees", &self.trees), ("folliage", &self.folliage), ("buildings", &self.buildings)] {
+            for (index, (_, chance)) in list.iter().enumerate() {
+                if !(0.0..=1.0).contains(chance) {
+                    return Err(format!("{}[{}]: the chance {} is not between 0 and 1", field, index, chance));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // every block a layer names has to be loaded
+    pub fn check_blocks(&self, blocks: &BlockRegistry) -> Result<(), String> {
+        for (index, (names, _)) in self.block_levels.iter().enumerate() {
+            for (block_index, name) in names.iter().enumerate() {
+                if blocks.parse_block(name).is_none() {
+                    return Err(format!("block_levels[{}][{}]: unknown block {}", index, block_index, name));
+                }
+            }
+        }
+        Ok(())
+    }
 
+    // structures that aren't loaded are skipped by the generator, these are only worth a warning
+    pub fn missing_structures<'a>(&'a self, structures: &'a Structures) -> impl Iterator<Item = String> + 'a {
+        [("trees", &self.trees), ("folliage", &self.folliage), ("buildings", &self.buildings)].into_iter()
+            .flat_map(|(field, list)| list.iter().enumerate().map(move |(index, entry)| (field, index, entry)))
+            .filter(|(_, _, (name, _))| structures.get(name).is_none())
+            .map(|(field, index, (name, _))| format!("{}[{}]: unknown structure {}", field, index, name))
+    }
+
+    // none when the biome uses the sea level of the world
+    pub fn get_sea_level(&self) -> Option<i64> {
+        (self.sea_level >= 0).then_some(self.sea_level)
+    }
 }
 
+// every loaded biome, in the order they were added
+#[derive(Clone, Default)]
 pub struct Biomes {
     biomes: Vec<Biome>
 }
@@ -18,4 +112,45 @@ impl Biomes {
             biomes: Vec::new()
         }
     }
-}
\ No newline at end of file
+
+    // a biome with the name of one that is already there replaces it, so mods can change biomes
+    pub fn add(&mut self, biome: Biome) {
+        match self.biomes.iter_mut().find(|existing| existing.name == biome.name) {
+            Some(existing) => *existing = biome,
+            None => self.biomes.push(biome)
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Biome> {
+        self.biomes.iter().find(|biome| biome.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    // the biome whose temperature and moisture are closest to the climate
+    pub fn closest(&self, climate: Climate) -> Option<&Biome> {
+        self.biomes.iter()
+            .min_by(|first, second| {
+                let first = climate.distance(first.temperature, first.moisture);
+                first.total_cmp(&climate.distance(second.temperature, second.moisture))
+            })
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.biomes.iter().map(|biome| biome.name.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Biome> {
+        self.biomes.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.biomes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.biomes.is_empty()
+    }
+}
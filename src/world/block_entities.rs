@@ -0,0 +1,193 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::world::chunk_map::BlockPosition;
+use crate::world::world::World;
+
+// extra data that belongs to a single block, like the contents of a chest.
+// block entities live in the chunk of their block and are saved with it
+pub trait BlockEntity: Send {
+    // the name the kind was registered with in BlockEntityKinds
+    fn kind(&self) -> &str;
+
+    // what gets written into the save, loaded again by the kind's constructor
+    fn save(&self) -> serde_json::Value;
+
+    fn clone_box(&self) -> Box<dyn BlockEntity>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    // the entity is not in its chunk while a hook runs, so hooks are free to change the world
+    fn on_place(&mut self, _world: &mut World, _position: BlockPosition) {}
+
+    fn on_break(&mut self, _world: &mut World, _position: BlockPosition) {}
+
+    // called before the chunk is saved and dropped
+    fn on_unload(&mut self, _world: &mut World, _position: BlockPosition) {}
+}
+impl Clone for Box<dyn BlockEntity> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+// builds a block entity from its saved data, null for a newly placed block
+pub type BlockEntityConstructor = fn(&serde_json::Value) -> Option<Box<dyn BlockEntity>>;
+
+// every kind of block entity, blocks pick theirs with "block_entity" in their json
+pub struct BlockEntityKinds {
+    constructors: HashMap<String, BlockEntityConstructor>
+}
+impl BlockEntityKinds {
+    pub fn new() -> Self {
+        let mut kinds = Self {
+            constructors: HashMap::new()
+        };
+        kinds.register("container", load_typed::<ContainerEntity>);
+        kinds.register("sign", load_typed::<SignEntity>);
+        kinds
+    }
+
+    pub fn register(&mut self, kind: &str, constructor: BlockEntityConstructor) {
+        self.constructors.insert(kind.to_string(), constructor);
+    }
+
+    pub fn contains(&self, kind: &str) -> bool {
+        self.constructors.contains_key(kind)
+    }
+
+    // a fresh block entity for a block that was just placed
+    pub fn create(&self, kind: &str) -> Option<Box<dyn BlockEntity>> {
+        self.constructors.get(kind).and_then(|constructor| constructor(&serde_json::Value::Null))
+    }
+
+    // kinds that aren't registered (a mod was removed) keep their data so it
+    // isn't lost the next time the chunk is saved
+    pub fn load(&self, kind: &str, data: serde_json::Value) -> Box<dyn BlockEntity> {
+        match self.constructors.get(kind).and_then(|constructor| constructor(&data)) {
+            Some(block_entity) => block_entity,
+            None => {
+                eprintln!("Failed to load a block entity of kind {}, keeping its data", kind);
+                Box::new(RawBlockEntity { kind: kind.to_string(), data })
+            }
+        }
+    }
+}
+impl Default for BlockEntityKinds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the constructor for any block entity that can go through serde
+pub fn load_typed<T: BlockEntity + DeserializeOwned + Default + 'static>(data: &serde_json::Value) -> Option<Box<dyn BlockEntity>> {
+    if data.is_null() {
+        return Some(Box::new(T::default()));
+    }
+    match serde_json::from_value::<T>(data.clone()) {
+        Ok(block_entity) => Some(Box::new(block_entity)),
+        Err(_) => None
+    }
+}
+
+// a block entity of a kind that isn't loaded
+#[derive(Clone)]
+pub struct RawBlockEntity {
+    pub kind: String,
+    pub data: serde_json::Value
+}
+impl BlockEntity for RawBlockEntity {
+    fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.data.clone()
+    }
+
+    fn clone_box(&self) -> Box<dyn BlockEntity> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub const CONTAINER_SLOTS: usize = 27;
+
+// a stack of items, stored by the name of the block
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ItemStack {
+    pub item: String,
+    pub count: u32
+}
+
+// chests and anything else that holds items
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ContainerEntity {
+    pub slots: Vec<Option<ItemStack>>
+}
+impl Default for ContainerEntity {
+    fn default() -> Self {
+        Self {
+            slots: vec![None; CONTAINER_SLOTS]
+        }
+    }
+}
+impl BlockEntity for ContainerEntity {
+    fn kind(&self) -> &str {
+        "container"
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn clone_box(&self) -> Box<dyn BlockEntity> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SignEntity {
+    pub lines: Vec<String>
+}
+impl BlockEntity for SignEntity {
+    fn kind(&self) -> &str {
+        "sign"
+    }
+
+    fn save(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn clone_box(&self) -> Box<dyn BlockEntity> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,264 @@
+use serde::Deserialize;
+
+use crate::world::blocks::{ElementRotation, ShapeElement, FACE_NORMALS};
+use crate::world::objects::chunk::BlockState;
+
+// a property a block can have, like a log's axis. the first value is the
+// default, every property takes as many bits of the packed state as its values need
+#[derive(Deserialize, Clone, Debug)]
+pub struct StateProperty {
+    pub name: String,
+    pub values: Vec<String>
+}
+impl StateProperty {
+    pub fn bits(&self) -> u32 {
+        if self.values.len() <= 1 {
+            return 0;
+        }
+        usize::BITS - (self.values.len() - 1).leading_zeros()
+    }
+}
+
+// where each property of a block sits in its packed state
+pub fn state_layout(properties: &[StateProperty]) -> impl Iterator<Item = (&StateProperty, u32)> {
+    properties.iter().scan(0, |shift, property| {
+        let property_shift = *shift;
+        *shift += property.bits();
+        Some((property, property_shift))
+    })
+}
+
+pub fn state_bits(properties: &[StateProperty]) -> u32 {
+    properties.iter().map(|property| property.bits()).sum()
+}
+
+pub fn get_state_value<'a>(properties: &'a [StateProperty], state: BlockState, name: &str) -> Option<&'a str> {
+    let (property, shift) = state_layout(properties).find(|(property, _)| property.name == name)?;
+    let index = (state >> shift) as usize & ((1 << property.bits()) - 1);
+    property.values.get(index).map(|value| value.as_str())
+}
+
+// none when the block has no such property or value
+pub fn with_state_value(properties: &[StateProperty], state: BlockState, name: &str, value: &str) -> Option<BlockState> {
+    let (property, shift) = state_layout(properties).find(|(property, _)| property.name == name)?;
+    let index = property.values.iter().position(|entry| entry == value)? as BlockState;
+    let mask = (((1u32 << property.bits()) - 1) << shift) as BlockState;
+    Some((state & !mask) | (index << shift))
+}
+
+// how a block is being placed, the face is the one of the clicked block in
+// FACE_NORMALS order, so the new block sits on the other side of it. the hit
+// point is where that face was clicked, relative to the new block (0 to 1)
+#[derive(Clone, Copy, Debug)]
+pub struct Placement {
+    pub face: usize,
+    pub hit: [f32; 3],
+    pub look_direction: [f32; 3]
+}
+
+// the state a block gets when it is placed, properties this doesn't know
+// about keep their default
+pub fn placement_state(properties: &[StateProperty], placement: &Placement) -> BlockState {
+    let normal = FACE_NORMALS[placement.face];
+    let mut state = 0;
+    for property in properties {
+        let value = match property.name.as_str() {
+            "axis" => ["x", "y", "z"][placement.face / 2],
+            "facing" if normal.1 != 0 && property.values.iter().any(|value| value == "up") => {
+                if normal.1 > 0 { "up" } else { "down" }
+            }
+            // blocks face the player that placed them
+            "facing" => {
+                let look = placement.look_direction;
+                if look[0].abs() > look[2].abs() {
+                    if look[0] > 0.0 { "west" } else { "east" }
+                } else if look[2] > 0.0 { "north" } else { "south" }
+            }
+            "half" => match normal.1 {
+                // placed against the underside of a block
+                -1 => "top",
+                1 => "bottom",
+                _ => if placement.hit[1] > 0.5 { "top" } else { "bottom" }
+            },
+            _ => continue
+        };
+        if let Some(new_state) = with_state_value(properties, state, &property.name, value) {
+            state = new_state;
+        }
+    }
+    state
+}
+
+const FACING_DIRECTIONS: [(&str, (i64, i64, i64)); 6] = [
+    ("east", (1, 0, 0)), ("west", (-1, 0, 0)),
+    ("up", (0, 1, 0)), ("down", (0, -1, 0)),
+    ("south", (0, 0, 1)), ("north", (0, 0, -1))
+];
+
+// the state of a block that was turned or mirrored along with the blocks
+// around it, like pasting a rotated copy. only axis and facing change
+pub fn transform_state(properties: &[StateProperty], state: BlockState, orientation: &Orientation) -> BlockState {
+    let mut new_state = state;
+    if let Some(axis) = get_state_value(properties, state, "axis") {
+        let unit = match axis { "x" => (1, 0, 0), "y" => (0, 1, 0), _ => (0, 0, 1) };
+        let rotated = orientation.apply_offset(unit);
+        let new_axis = if rotated.0 != 0 { "x" } else if rotated.1 != 0 { "y" } else { "z" };
+        new_state = with_state_value(properties, new_state, "axis", new_axis).unwrap_or(new_state);
+    }
+    if let Some(facing) = get_state_value(properties, state, "facing") {
+        if let Some((_, direction)) = FACING_DIRECTIONS.iter().find(|(name, _)| *name == facing) {
+            let rotated = orientation.apply_offset(*direction);
+            if let Some((new_facing, _)) = FACING_DIRECTIONS.iter().find(|(_, direction)| *direction == rotated) {
+                new_state = with_state_value(properties, new_state, "facing", new_facing).unwrap_or(new_state);
+            }
+        }
+    }
+    new_state
+}
+
+// a 90 degree rotation or mirror of a block, stored as a signed permutation
+// matrix that maps shape space to world space
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Orientation {
+    matrix: [[i8; 3]; 3]
+}
+impl Orientation {
+    pub const IDENTITY: Self = Self { matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]] };
+
+    // counter clockwise quarter turns around an axis
+    pub fn quarter_turns(axis: usize, turns: i32) -> Self {
+        let turn = match axis {
+            0 => [[1, 0, 0], [0, 0, -1], [0, 1, 0]],
+            1 => [[0, 0, 1], [0, 1, 0], [-1, 0, 0]],
+            _ => [[0, -1, 0], [1, 0, 0], [0, 0, 1]]
+        };
+        let mut orientation = Self::IDENTITY;
+        for _ in 0..turns.rem_euclid(4) {
+            orientation = orientation.then(Self { matrix: turn });
+        }
+        orientation
+    }
+
+    pub fn mirror(axis: usize) -> Self {
+        let mut orientation = Self::IDENTITY;
+        orientation.matrix[axis][axis] = -1;
+        orientation
+    }
+
+    // the orientation a block's state puts it in, logs lie along their axis,
+    // facing turns the block so its front (north) points that way and the top
+    // half of a slab is the bottom half mirrored
+    pub fn from_state(properties: &[StateProperty], state: BlockState) -> Self {
+        let mut orientation = Self::IDENTITY;
+        match get_state_value(properties, state, "axis") {
+            Some("x") => orientation = orientation.then(Self::quarter_turns(2, 3)),
+            Some("z") => orientation = orientation.then(Self::quarter_turns(0, 1)),
+            _ => {}
+        }
+        match get_state_value(properties, state, "facing") {
+            Some("west") => orientation = orientation.then(Self::quarter_turns(1, 1)),
+            Some("south") => orientation = orientation.then(Self::quarter_turns(1, 2)),
+            Some("east") => orientation = orientation.then(Self::quarter_turns(1, 3)),
+            Some("up") => orientation = orientation.then(Self::quarter_turns(0, 1)),
+            Some("down") => orientation = orientation.then(Self::quarter_turns(0, 3)),
+            _ => {}
+        }
+        if get_state_value(properties, state, "half") == Some("top") {
+            orientation = orientation.then(Self::mirror(1));
+        }
+        orientation
+    }
+
+    // applies this orientation and then the other one
+    pub fn then(self, other: Self) -> Self {
+        let mut matrix = [[0; 3]; 3];
+        for (row, values) in matrix.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = (0..3).map(|index| other.matrix[row][index] * self.matrix[index][column]).sum();
+            }
+        }
+        Self { matrix }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    pub fn determinant(&self) -> i8 {
+        let m = self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn apply(&self, vector: [f32; 3]) -> [f32; 3] {
+        let mut result = [0.0; 3];
+        for (row, value) in result.iter_mut().enumerate() {
+            *value = (0..3).map(|index| self.matrix[row][index] as f32 * vector[index]).sum();
+        }
+        result
+    }
+
+    pub fn apply_offset(&self, offset: (i64, i64, i64)) -> (i64, i64, i64) {
+        let vector = [offset.0, offset.1, offset.2];
+        let mut result = [0; 3];
+        for (row, value) in result.iter_mut().enumerate() {
+            *value = (0..3).map(|index| self.matrix[row][index] as i64 * vector[index]).sum();
+        }
+        (result[0], result[1], result[2])
+    }
+
+    // rotates a point of a shape (0 to 16) around the center of the block
+    pub fn apply_point(&self, point: [f32; 3]) -> [f32; 3] {
+        let rotated = self.apply([point[0] - 8.0, point[1] - 8.0, point[2] - 8.0]);
+        [rotated[0] + 8.0, rotated[1] + 8.0, rotated[2] + 8.0]
+    }
+
+    // where a face in FACE_NORMALS order ends up
+    pub fn apply_face(&self, face: usize) -> usize {
+        let normal = FACE_NORMALS[face];
+        let rotated = self.apply([normal.0 as f32, normal.1 as f32, normal.2 as f32]);
+        let rotated = (rotated[0] as i64, rotated[1] as i64, rotated[2] as i64);
+        FACE_NORMALS.iter().position(|normal| *normal == rotated).unwrap_or(face)
+    }
+
+    // the face textures of a block, moved to where their faces ended up
+    pub fn apply_faces<T: Copy>(&self, faces: [T; 6]) -> [T; 6] {
+        let mut result = faces;
+        for (face, value) in faces.iter().enumerate() {
+            result[self.apply_face(face)] = *value;
+        }
+        result
+    }
+
+    // the element as it sits in the block, it stays a box because the
+    // orientation only ever turns by 90 degrees
+    pub fn apply_element(&self, element: &ShapeElement) -> ShapeElement {
+        let from = self.apply_point(element.from);
+        let to = self.apply_point(element.to);
+        let rotation = element.rotation.as_ref().map(|rotation| {
+            let axis = match rotation.axis.as_str() { "x" => 0, "y" => 1, _ => 2 };
+            let mut unit = [0.0; 3];
+            unit[axis] = 1.0;
+            let rotated = self.apply(unit);
+            let new_axis = rotated.iter().position(|value| *value != 0.0).unwrap_or(axis);
+            // turning the axis around or mirroring the element flips the angle
+            let sign = rotated[new_axis] * self.determinant() as f32;
+            ElementRotation {
+                angle: rotation.angle * sign,
+                axis: ["x", "y", "z"][new_axis].to_string(),
+                origin: self.apply_point(rotation.origin)
+            }
+        });
+        ShapeElement {
+            from: [from[0].min(to[0]), from[1].min(to[1]), from[2].min(to[2])],
+            to: [from[0].max(to[0]), from[1].max(to[1]), from[2].max(to[2])],
+            rotation
+        }
+    }
+}
+impl Default for Orientation {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::world::blocks::BlockRegistry;
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition};
+use crate::world::falling_blocks::FallingBlock;
+use crate::world::objects::chunk::{Block, AIR};
+use crate::world::world::World;
+
+// why a block is being updated, handlers can ignore the ones they don't care about
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum UpdateReason {
+    // the block itself was just put there
+    Placed,
+    // one of the six blocks next to it changed
+    NeighbourChanged,
+    // something asked for it with World::schedule_update
+    Requested
+}
+impl UpdateReason {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            UpdateReason::Placed => 0,
+            UpdateReason::NeighbourChanged => 1,
+            UpdateReason::Requested => 2
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(UpdateReason::Placed),
+            1 => Some(UpdateReason::NeighbourChanged),
+            2 => Some(UpdateReason::Requested),
+            _ => None
+        }
+    }
+}
+
+// how a block reacts to changes around it, blocks set theirs with
+// "block_update" in their json. anything besides the handler and the delay
+// is up to the handler
+#[derive(Deserialize, Clone, Debug)]
+pub struct BlockUpdateDef {
+    pub handler: String,
+    // ticks between the change and the update
+    #[serde(default = "one_tick")]
+    pub delay: u64,
+    #[serde(flatten)]
+    pub options: HashMap<String, serde_json::Value>
+}
+
+fn one_tick() -> u64 {
+    1
+}
+
+// runs the update of the block at the position
+pub type BlockUpdateHandler = fn(&mut World, BlockPosition, Block, UpdateReason, &BlockUpdateDef);
+
+// every handler a block update can name, mods add theirs with register
+pub struct BlockUpdateHandlers {
+    handlers: HashMap<String, BlockUpdateHandler>
+}
+impl BlockUpdateHandlers {
+    pub fn new() -> Self {
+        let mut handlers = Self {
+            handlers: HashMap::new()
+        };
+        handlers.register("gravity", gravity);
+        handlers
+    }
+
+    pub fn register(&mut self, name: &str, handler: BlockUpdateHandler) {
+        self.handlers.insert(name.to_string(), handler);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<BlockUpdateHandler> {
+        self.handlers.get(name).copied()
+    }
+}
+impl Default for BlockUpdateHandlers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// a block update that is still waiting, with the ticks it has left
+pub type SavedUpdate = (BlockPosition, u64, UpdateReason);
+
+// block updates by the tick they are due. updates of chunks that get
+// unloaded wait with the time they had left until the chunk is back
+#[derive(Clone, Default)]
+pub struct ScheduledUpdates {
+    scheduled: BTreeMap<u64, Vec<(BlockPosition, UpdateReason)>>,
+    positions: HashSet<BlockPosition>,
+    waiting: HashMap<ChunkPosition, Vec<SavedUpdate>>
+}
+impl ScheduledUpdates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // everything from a save waits, no chunk is loaded yet when it is read
+    pub fn from_saved(updates: Vec<SavedUpdate>) -> Self {
+        let mut scheduled = Self::new();
+        for update in updates {
+            scheduled.waiting.entry(ChunkMap::chunk_position(update.0)).or_default().push(update);
+        }
+        scheduled
+    }
+
+    // a block that is already waiting keeps its earlier update
+    pub fn schedule(&mut self, position: BlockPosition, tick: u64, reason: UpdateReason) {
+        if self.positions.insert(position) {
+            self.scheduled.entry(tick).or_default().push((position, reason));
+        }
+    }
+
+    // at most limit updates that are due, the rest stay for the next tick
+    pub fn take_due(&mut self, tick: u64, limit: usize) -> Vec<(BlockPosition, UpdateReason)> {
+        let mut due = Vec::new();
+        while due.len() < limit {
+            let Some(mut entry) = self.scheduled.first_entry() else { break; };
+            if *entry.key() > tick {
+                break;
+            }
+            let updates = entry.get_mut();
+            let count = updates.len().min(limit - due.len());
+            due.extend(updates.drain(..count));
+            if updates.is_empty() {
+                entry.remove();
+            }
+        }
+        for (position, _) in &due {
+            self.positions.remove(position);
+        }
+        due
+    }
+
+    // puts the updates of a chunk aside until it is loaded again
+    pub fn unload_chunk(&mut self, chunk_position: ChunkPosition, tick: u64) {
+        let mut unloaded = Vec::new();
+        self.scheduled.retain(|due, updates| {
+            updates.retain(|(position, reason)| {
+                if ChunkMap::chunk_position(*position) != chunk_position {
+                    return true;
+                }
+                unloaded.push((*position, due.saturating_sub(tick), *reason));
+                false
+            });
+            !updates.is_empty()
+        });
+        for (position, _, _) in &unloaded {
+            self.positions.remove(position);
+        }
+        if !unloaded.is_empty() {
+            self.waiting.entry(chunk_position).or_default().extend(unloaded);
+        }
+    }
+
+    pub fn load_chunk(&mut self, chunk_position: ChunkPosition, tick: u64) {
+        for (position, delay, reason) in self.waiting.remove(&chunk_position).unwrap_or_default() {
+            self.schedule(position, tick + delay, reason);
+        }
+    }
+
+    // every update with the ticks it has left, for saving
+    pub fn to_saved(&self, tick: u64) -> Vec<SavedUpdate> {
+        let scheduled = self.scheduled.iter()
+            .flat_map(|(due, updates)| updates.iter().map(move |(position, reason)| (*position, due.saturating_sub(tick), *reason)));
+        scheduled.chain(self.waiting.values().flatten().copied()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len() + self.waiting.values().map(|updates| updates.len()).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// runs the handler of a block whose update is due, the block may have
+// changed since it was scheduled so its current handler is the one that runs
+pub fn update_block(world: &mut World, position: BlockPosition, reason: UpdateReason) {
+    let Some(block) = world.get_block(position) else {
+        return;
+    };
+    let Some(update) = world.get_blocks().get(block.id).and_then(|definition| definition.block_update.clone()) else {
+        return;
+    };
+    // handlers of a mod that was removed are skipped
+    if let Some(handler) = world.get_block_update_handlers().get(&update.handler) {
+        handler(world, position, block, reason, &update);
+    }
+}
+
+// whether a falling block passes through this block, and may replace it when it lands
+pub fn can_fall_into(blocks: &BlockRegistry, block: Block) -> bool {
+    if block.is_air() || blocks.fluid(block).is_some() {
+        return true;
+    }
+    !blocks.collides(block) && blocks.get(block.id).is_none_or(|definition| definition.block_entity.is_none())
+}
+
+// blocks like sand fall when there is nothing below them, they leave the
+// grid while falling and are placed again where they land
+fn gravity(world: &mut World, position: BlockPosition, block: Block, _reason: UpdateReason, _update: &BlockUpdateDef) {
+    let below = world.get_block((position.0, position.1 - 1, position.2));
+    if !below.is_some_and(|below| can_fall_into(world.get_blocks(), below)) {
+        return;
+    }
+    world.set_block(position, AIR);
+    world.add_falling_block(FallingBlock::new(block, position));
+}
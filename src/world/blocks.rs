@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::world::block_states::{self, Orientation, Placement, StateProperty};
+use crate::world::block_updates::BlockUpdateDef;
+use crate::world::fluids::FluidDef;
+use crate::world::light::MAX_LIGHT;
+use crate::world::objects::chunk::{Block, BlockId, BlockState, AIR};
+use crate::world::random_ticks::RandomTickDef;
+
+// blocks with this state are their shape piled up that many times, like snow layers
+pub const LAYERS_STATE: &str = "layers";
+
+// the order faces are stored in everywhere: right, left, top, bottom, front, back
+pub const FACE_NORMALS: [(i64, i64, i64); 6] = [
+    (1, 0, 0), (-1, 0, 0),
+    (0, 1, 0), (0, -1, 0),
+    (0, 0, 1), (0, 0, -1)
+];
+
+// block models can either give one texture, one for the top, sides and
+// bottom, or one for every face
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum BlockTextures {
+    Single { top: u16, left: u16, right: u16, front: u16, back: u16, bottom: u16 },
+    Individual { top: u16, sides: u16, bottom: u16 },
+    Uniform { all: u16 }
+}
+impl BlockTextures {
+    // atlas index for every face, in FACE_NORMALS order
+    pub fn faces(&self) -> [u16; 6] {
+        match self {
+            BlockTextures::Single { top, left, right, front, back, bottom } => [*right, *left, *top, *bottom, *front, *back],
+            BlockTextures::Individual { top, sides, bottom } => [*sides, *sides, *top, *bottom, *sides, *sides],
+            BlockTextures::Uniform { all } => [*all; 6]
+        }
+    }
+}
+
+// a block as it is described in assets/models/blocks
+#[derive(Deserialize, Clone, Debug)]
+pub struct BlockDef {
+    #[serde(rename = "block_name")]
+    pub name: String,
+    pub creator: String,
+    pub textures: BlockTextures,
+    pub shape: String,
+    // when set the faces of neighbouring blocks stay visible through this one
+    #[serde(default)]
+    pub sides: bool,
+    #[serde(default)]
+    pub transparent: bool,
+    #[serde(default)]
+    pub collide: bool,
+    // how much block light it gives off, up to MAX_LIGHT
+    #[serde(default)]
+    pub light_level: u8,
+    // the colour of that light as red, green and blue from 0 to 255, white when not set
+    #[serde(default)]
+    pub light_color: Option<[u8; 3]>,
+    // set for water, lava and other blocks that flow
+    #[serde(default)]
+    pub fluid: Option<FluidDef>,
+    // what it does when a random tick picks it, like dirt turning into grass
+    #[serde(default)]
+    pub random_ticks: Vec<RandomTickDef>,
+    // what it does when a block next to it changes, like sand falling
+    #[serde(default)]
+    pub block_update: Option<BlockUpdateDef>,
+    // axis, facing, half, waterlogged or anything a mod needs
+    #[serde(default)]
+    pub states: Vec<StateProperty>,
+    // the kind of block entity every block of this type gets
+    #[serde(default)]
+    pub block_entity: Option<String>,
+    // anything else in the block file, so new properties don't need a new field right away
+    #[serde(flatten)]
+    pub properties: HashMap<String, serde_json::Value>
+}
+impl BlockDef {
+    pub fn air() -> Self {
+        Self {
+            name: "air".to_string(),
+            creator: "polydural".to_string(),
+            textures: BlockTextures::Uniform { all: 0 },
+            shape: "none".to_string(),
+            sides: true,
+            transparent: true,
+            collide: false,
+            light_level: 0,
+            light_color: None,
+            fluid: None,
+            random_ticks: Vec::new(),
+            block_update: None,
+            states: Vec::new(),
+            block_entity: None,
+            properties: HashMap::new()
+        }
+    }
+
+    // creator:name, this is what gets saved so mods can't clash on names
+    pub fn full_name(&self) -> String {
+        format!("{}:{}", self.creator, self.name)
+    }
+
+    // whether this block hides the faces of the blocks next to it
+    pub fn occludes(&self) -> bool {
+        !self.sides && !self.transparent
+    }
+
+    pub fn get_property(&self, name: &str) -> Option<&serde_json::Value> {
+        self.properties.get(name)
+    }
+
+    pub fn get_state_value(&self, state: BlockState, name: &str) -> Option<&str> {
+        block_states::get_state_value(&self.states, state, name)
+    }
+
+    pub fn with_state_value(&self, state: BlockState, name: &str, value: &str) -> Option<BlockState> {
+        block_states::with_state_value(&self.states, state, name, value)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ElementRotation {
+    pub angle: f32,
+    pub axis: String,
+    pub origin: [f32; 3]
+}
+
+// a box of a shape, coordinates go from 0 to 16 over one block
+#[derive(Deserialize, Clone, Debug)]
+pub struct ShapeElement {
+    pub from: [f32; 3],
+    pub to: [f32; 3],
+    #[serde(default)]
+    pub rotation: Option<ElementRotation>
+}
+impl ShapeElement {
+    // the element piled on top of itself, it never grows out of the block
+    pub fn stacked(&self, layers: u32) -> Self {
+        let mut to = self.to;
+        to[1] = (self.from[1] + (self.to[1] - self.from[1]) * layers as f32).min(16.0);
+        Self {
+            to,
+            ..self.clone()
+        }
+    }
+}
+
+// a shape as it is described in assets/models/shapes
+#[derive(Deserialize, Clone, Debug)]
+pub struct ShapeDef {
+    #[serde(rename = "shape_name")]
+    pub name: String,
+    pub elements: Vec<ShapeElement>
+}
+
+// every known block, indexed by the id it is stored with in chunks. id 0 is
+// always air. ids are handed out in load order, but a world save keeps its
+// own name to id map which is applied with assign_ids so saved chunks keep
+// pointing at the right blocks when mods are added or removed
+pub struct BlockRegistry {
+    // none for ids a save knows about but no loaded block provides
+    blocks: Vec<Option<BlockDef>>,
+    full_names: HashMap<String, BlockId>,
+    names: HashMap<String, BlockId>,
+    // ids of saved blocks that aren't loaded, kept so they aren't handed out again
+    missing_ids: HashMap<String, BlockId>,
+    shapes: HashMap<String, ShapeDef>
+}
+impl BlockRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            blocks: Vec::new(),
+            full_names: HashMap::new(),
+            names: HashMap::new(),
+            missing_ids: HashMap::new(),
+            shapes: HashMap::new()
+        };
+        registry.register(BlockDef::air());
+        registry
+    }
+
+    // adds a block, a block with the same creator and name is only added once
+    pub fn register(&mut self, block: BlockDef) -> BlockId {
+        if let Some(id) = self.full_names.get(&block.full_name()) {
+            return *id;
+        }
+
+        let id = self.blocks.len() as BlockId;
+        self.full_names.insert(block.full_name(), id);
+        self.names.entry(block.name.clone()).or_insert(id);
+        self.blocks.push(Some(block));
+        id
+    }
+
+    pub fn add_shape(&mut self, shape: ShapeDef) {
+        self.shapes.entry(shape.name.clone()).or_insert(shape);
+    }
+
+    // renumbers the blocks so every block in the saved map keeps its id,
+    // blocks the save doesn't know yet get the ids after that. has to happen
+    // before any chunk is loaded
+    pub fn assign_ids(&mut self, saved_ids: &HashMap<String, BlockId>) {
+        let mut blocks: Vec<Option<BlockDef>> = vec![None; saved_ids.values().max().map_or(1, |id| *id as usize + 1)];
+        blocks[AIR.id as usize] = Some(BlockDef::air());
+
+        let mut remaining = Vec::new();
+        for block in self.blocks.drain(..).flatten() {
+            match saved_ids.get(&block.full_name()) {
+                Some(id) if *id != AIR.id => blocks[*id as usize] = Some(block),
+                _ if block.full_name() == BlockDef::air().full_name() => {}
+                _ => remaining.push(block)
+            }
+        }
+        blocks.extend(remaining.into_iter().map(Some));
+
+        self.blocks = blocks;
+        self.full_names.clear();
+        self.names.clear();
+        for (id, block) in self.blocks.iter().enumerate() {
+            if let Some(block) = block {
+                self.full_names.insert(block.full_name(), id as BlockId);
+                self.names.entry(block.name.clone()).or_insert(id as BlockId);
+            }
+        }
+        self.missing_ids.clear();
+        for (full_name, id) in saved_ids {
+            if self.blocks[*id as usize].is_none() {
+                println!("Block {} is missing, it will show up as air", full_name);
+                self.missing_ids.insert(full_name.clone(), *id);
+            }
+        }
+    }
+
+    // creator:name to id for every block, this is what a save stores
+    pub fn id_map(&self) -> HashMap<String, BlockId> {
+        let mut ids = self.missing_ids.clone();
+        ids.extend(self.full_names.iter().map(|(full_name, id)| (full_name.clone(), *id)));
+        ids
+    }
+
+    pub fn get(&self, id: BlockId) -> Option<&BlockDef> {
+        self.blocks.get(id as usize).and_then(|block| block.as_ref())
+    }
+
+    // takes either creator:name or just the name
+    pub fn get_id(&self, name: &str) -> Option<BlockId> {
+        if name.contains(':') {
+            self.full_names.get(name).copied()
+        } else {
+            self.names.get(name).copied()
+        }
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&BlockDef> {
+        self.get_id(name).and_then(|id| self.get(id))
+    }
+
+    pub fn get_shape(&self, name: &str) -> Option<&ShapeDef> {
+        self.shapes.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.len() <= 1
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BlockId, &BlockDef)> {
+        self.blocks.iter().enumerate().filter_map(|(id, block)| block.as_ref().map(|block| (id as BlockId, block)))
+    }
+
+    // unknown ids behave like air everywhere
+    pub fn occludes(&self, block: Block) -> bool {
+        self.get(block.id).is_some_and(|block| block.occludes())
+    }
+
+    pub fn collides(&self, block: Block) -> bool {
+        self.get(block.id).is_some_and(|block| block.collide)
+    }
+
+    pub fn is_transparent(&self, block: Block) -> bool {
+        self.get(block.id).is_none_or(|block| block.transparent)
+    }
+
+    // whether sky light stops at this block
+    pub fn blocks_light(&self, block: Block) -> bool {
+        !self.is_transparent(block)
+    }
+
+    pub fn fluid(&self, block: Block) -> Option<&FluidDef> {
+        self.get(block.id).and_then(|block| block.fluid.as_ref())
+    }
+
+    pub fn has_random_ticks(&self, block: Block) -> bool {
+        self.get(block.id).is_some_and(|block| !block.random_ticks.is_empty())
+    }
+
+    // the red, green and blue block light levels it gives off
+    pub fn emission(&self, block: Block) -> [u8; 3] {
+        let Some(block) = self.get(block.id) else {
+            return [0; 3];
+        };
+        let level = block.light_level.min(MAX_LIGHT);
+        match block.light_color {
+            Some(color) => color.map(|channel| (level as f32 * channel as f32 / 255.0).round() as u8),
+            None => [level; 3]
+        }
+    }
+
+    // the block with the state it should get when placed like this
+    pub fn placement_block(&self, id: BlockId, placement: &Placement) -> Block {
+        match self.get(id) {
+            Some(block) => Block::new(id, block_states::placement_state(&block.states, placement)),
+            None => Block::from_id(id)
+        }
+    }
+
+    // the block as it is after being turned or mirrored with its surroundings
+    pub fn transform_block(&self, block: Block, orientation: &Orientation) -> Block {
+        match self.get(block.id) {
+            Some(definition) if !definition.states.is_empty() => {
+                Block::new(block.id, block_states::transform_state(&definition.states, block.state, orientation))
+            }
+            _ => block
+        }
+    }
+
+    pub fn orientation(&self, block: Block) -> Orientation {
+        match self.get(block.id) {
+            Some(definition) if !definition.states.is_empty() => Orientation::from_state(&definition.states, block.state),
+            _ => Orientation::IDENTITY
+        }
+    }
+
+    // how many layers a block has, 1 for blocks without the layers state
+    pub fn layers(&self, block: Block) -> u32 {
+        self.get(block.id)
+            .and_then(|definition| definition.get_state_value(block.state, LAYERS_STATE))
+            .and_then(|layers| layers.parse().ok())
+            .unwrap_or(1)
+    }
+
+    // the boxes a block collides with, from 0 to 1 inside the block. flat and
+    // rotated elements like grass never collide
+    pub fn collision_boxes(&self, block: Block) -> Vec<([f32; 3], [f32; 3])> {
+        let Some(definition) = self.get(block.id).filter(|definition| definition.collide) else {
+            return Vec::new();
+        };
+        let Some(shape) = self.get_shape(&definition.shape) else {
+            return Vec::new();
+        };
+        let orientation = self.orientation(block);
+        let layers = self.layers(block);
+        shape.elements.iter()
+            .map(|element| orientation.apply_element(&element.stacked(layers)))
+            .filter(|element| element.rotation.as_ref().is_none_or(|rotation| rotation.angle == 0.0))
+            .filter(|element| (0..3).all(|axis| element.from[axis] < element.to[axis]))
+            .map(|element| (element.from.map(|value| value / 16.0), element.to.map(|value| value / 16.0)))
+            .collect()
+    }
+
+    // reads blocks the way commands and structure files write them, either
+    // a bare name or something like polydural:oak_log[axis=x]
+    pub fn parse_block(&self, text: &str) -> Option<Block> {
+        let text = text.trim();
+        let (name, properties) = match text.split_once('[') {
+            Some((name, properties)) => (name, properties.strip_suffix(']')?),
+            None => (text, "")
+        };
+        let id = self.get_id(name)?;
+        let definition = self.get(id)?;
+
+        let mut state = 0;
+        for property in properties.split(',').filter(|property| !property.trim().is_empty()) {
+            let (property, value) = property.split_once('=')?;
+            state = definition.with_state_value(state, property.trim(), value.trim())?;
+        }
+        Some(Block::new(id, state))
+    }
+
+    // the opposite of parse_block, blocks in their default state are written without states
+    pub fn format_block(&self, block: Block) -> String {
+        let Some(definition) = self.get(block.id) else {
+            return format!("unknown:{}", block.id);
+        };
+        if block.state == 0 {
+            return definition.full_name();
+        }
+        let properties: Vec<String> = definition.states.iter()
+            .filter_map(|property| {
+                let value = definition.get_state_value(block.state, &property.name)?;
+                Some(format!("{}={}", property.name, value))
+            })
+            .collect();
+        format!("{}[{}]", definition.full_name(), properties.join(","))
+    }
+}
+impl Default for BlockRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::config::{CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+use crate::world::block_entities::BlockEntity;
+use crate::world::light::{ChunkLight, LightChannel};
+use crate::world::objects::chunk::{Block, Chunk};
+
+pub type ChunkPosition = (i64, i64, i64);
+pub type BlockPosition = (i64, i64, i64);
+
+// west, east, down, up, north, south
+pub const NEIGHBOUR_OFFSETS: [(i64, i64, i64); 6] = [
+    (-1, 0, 0), (1, 0, 0),
+    (0, -1, 0), (0, 1, 0),
+    (0, 0, -1), (0, 0, 1)
+];
+
+// all loaded chunks of a world, this is the one place blocks are looked up by
+// their world position so physics, meshing and generation agree on borders
+pub struct ChunkMap {
+    chunks: HashMap<ChunkPosition, Chunk>,
+    // chunks that changed since they were last written to disk
+    unsaved: HashSet<ChunkPosition>,
+    // chunks whose mesh is out of date
+    changed: HashSet<ChunkPosition>,
+    // the heights of the loaded chunks of every column, so the top of a
+    // column is found without looking at every chunk
+    columns: HashMap<(i64, i64), BTreeSet<i64>>,
+    // kept next to the blocks, light changes don't make a chunk unsaved
+    light: HashMap<ChunkPosition, ChunkLight>
+}
+impl ChunkMap {
+    pub fn new() -> Self {
+        Self {
+            chunks: HashMap::new(),
+            unsaved: HashSet::new(),
+            changed: HashSet::new(),
+            columns: HashMap::new(),
+            light: HashMap::new()
+        }
+    }
+
+    // floor division, so block -1 lands in chunk -1 instead of chunk 0
+    pub fn chunk_position(position: BlockPosition) -> ChunkPosition {
+        (
+            position.0.div_euclid(CHUNK_SIZE_X as i64),
+            position.1.div_euclid(CHUNK_SIZE_Y as i64),
+            position.2.div_euclid(CHUNK_SIZE_Z as i64)
+        )
+    }
+
+    pub fn local_position(position: BlockPosition) -> (usize, usize, usize) {
+        (
+            position.0.rem_euclid(CHUNK_SIZE_X as i64) as usize,
+            position.1.rem_euclid(CHUNK_SIZE_Y as i64) as usize,
+            position.2.rem_euclid(CHUNK_SIZE_Z as i64) as usize
+        )
+    }
+
+    pub fn world_position(chunk_position: ChunkPosition, local_position: (usize, usize, usize)) -> BlockPosition {
+        (
+            chunk_position.0 * CHUNK_SIZE_X as i64 + local_position.0 as i64,
+            chunk_position.1 * CHUNK_SIZE_Y as i64 + local_position.1 as i64,
+            chunk_position.2 * CHUNK_SIZE_Z as i64 + local_position.2 as i64
+        )
+    }
+
+    // blocks are centered on their position, so a block spans -0.5..0.5 around it
+    pub fn block_at_point(x: f32, y: f32, z: f32) -> BlockPosition {
+        ((x + 0.5).floor() as i64, (y + 0.5).floor() as i64, (z + 0.5).floor() as i64)
+    }
+
+    pub fn get_chunk(&self, position: ChunkPosition) -> Option<&Chunk> {
+        self.chunks.get(&position)
+    }
+
+    pub fn get_chunk_mut(&mut self, position: ChunkPosition) -> Option<&mut Chunk> {
+        let chunk = self.chunks.get_mut(&position);
+        if chunk.is_some() {
+            self.unsaved.insert(position);
+        }
+        chunk
+    }
+
+    // neighbours get remeshed too, their faces on the shared border were hidden until now
+    pub fn insert_chunk(&mut self, position: ChunkPosition, chunk: Chunk) -> Option<Chunk> {
+        self.unsaved.insert(position);
+        self.changed.insert(position);
+        for offset in NEIGHBOUR_OFFSETS {
+            let neighbour = (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2);
+            if self.chunks.contains_key(&neighbour) {
+                self.changed.insert(neighbour);
+            }
+        }
+        self.columns.entry((position.0, position.2)).or_default().insert(position.1);
+        self.chunks.insert(position, chunk)
+    }
+
+    pub fn remove_chunk(&mut self, position: ChunkPosition) -> Option<Chunk> {
+        self.unsaved.remove(&position);
+        self.changed.insert(position);
+        if let Some(column) = self.columns.get_mut(&(position.0, position.2)) {
+            column.remove(&position.1);
+            if column.is_empty() {
+                self.columns.remove(&(position.0, position.2));
+            }
+        }
+        self.light.remove(&position);
+        self.chunks.remove(&position)
+    }
+
+    // the chunk heights that are loaded in a column of chunks, from the top down
+    pub fn column_chunks(&self, chunk_x: i64, chunk_z: i64) -> impl Iterator<Item = i64> + '_ {
+        self.columns.get(&(chunk_x, chunk_z)).into_iter().flat_map(|column| column.iter().rev().copied())
+    }
+
+    // chunk x and z of every column with at least one loaded chunk
+    pub fn columns(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.columns.keys().copied()
+    }
+
+    // marks the chunks that can see a block as needing a new mesh, blocks on
+    // a chunk border also change the faces and shading of the chunk next to it
+    pub fn mark_block_changed(&mut self, position: BlockPosition) {
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let chunk_position = Self::chunk_position((position.0 + x, position.1 + y, position.2 + z));
+                    if self.chunks.contains_key(&chunk_position) {
+                        self.changed.insert(chunk_position);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn mark_changed(&mut self, position: ChunkPosition) {
+        self.changed.insert(position);
+    }
+
+    // every chunk that needs a new mesh since the last call, a chunk that
+    // isn't loaded anymore in here should have its mesh removed
+    pub fn take_changed(&mut self) -> Vec<ChunkPosition> {
+        self.changed.drain().collect()
+    }
+
+    pub fn is_unsaved(&self, position: ChunkPosition) -> bool {
+        self.unsaved.contains(&position)
+    }
+
+    pub fn mark_saved(&mut self, position: ChunkPosition) {
+        self.unsaved.remove(&position);
+    }
+
+    pub fn unsaved_positions(&self) -> Vec<ChunkPosition> {
+        self.unsaved.iter().copied().collect()
+    }
+
+    pub fn contains_chunk(&self, position: ChunkPosition) -> bool {
+        self.chunks.contains_key(&position)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = ChunkPosition> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&ChunkPosition, &Chunk)> {
+        self.chunks.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn get_neighbour(&self, position: ChunkPosition, offset: (i64, i64, i64)) -> Option<&Chunk> {
+        self.get_chunk((position.0 + offset.0, position.1 + offset.1, position.2 + offset.2))
+    }
+
+    // the six face neighbours in the order of NEIGHBOUR_OFFSETS
+    pub fn get_neighbours(&self, position: ChunkPosition) -> [Option<&Chunk>; 6] {
+        NEIGHBOUR_OFFSETS.map(|offset| self.get_neighbour(position, offset))
+    }
+
+    // none when the chunk holding the block is not loaded
+    pub fn get_block(&self, position: BlockPosition) -> Option<Block> {
+        let (x, y, z) = Self::local_position(position);
+        self.get_chunk(Self::chunk_position(position)).map(|chunk| chunk.get(x, y, z))
+    }
+
+    // returns the block that was replaced, or none when the chunk is not loaded
+    pub fn set_block(&mut self, position: BlockPosition, block: Block) -> Option<Block> {
+        let (x, y, z) = Self::local_position(position);
+        let old_block = self.get_chunk_mut(Self::chunk_position(position)).map(|chunk| chunk.set(x, y, z, block));
+        if old_block.is_some_and(|old_block| old_block != block) {
+            self.mark_block_changed(position);
+        }
+        old_block
+    }
+
+    pub fn get_block_entity(&self, position: BlockPosition) -> Option<&dyn BlockEntity> {
+        let (x, y, z) = Self::local_position(position);
+        self.get_chunk(Self::chunk_position(position)).and_then(|chunk| chunk.get_block_entity(x, y, z))
+    }
+
+    // marks the chunk unsaved, the caller is expected to change the block entity
+    pub fn get_block_entity_mut(&mut self, position: BlockPosition) -> Option<&mut (dyn BlockEntity + 'static)> {
+        let (x, y, z) = Self::local_position(position);
+        self.get_chunk_mut(Self::chunk_position(position)).and_then(|chunk| chunk.get_block_entity_mut(x, y, z))
+    }
+
+    // gives the block entity back when the chunk is not loaded
+    pub fn set_block_entity(&mut self, position: BlockPosition, block_entity: Box<dyn BlockEntity>) -> Result<Option<Box<dyn BlockEntity>>, Box<dyn BlockEntity>> {
+        let (x, y, z) = Self::local_position(position);
+        match self.get_chunk_mut(Self::chunk_position(position)) {
+            Some(chunk) => Ok(chunk.set_block_entity(x, y, z, block_entity)),
+            None => Err(block_entity)
+        }
+    }
+
+    pub fn remove_block_entity(&mut self, position: BlockPosition) -> Option<Box<dyn BlockEntity>> {
+        let (x, y, z) = Self::local_position(position);
+        let chunk_position = Self::chunk_position(position);
+        // only a chunk that had one is marked unsaved
+        self.chunks.get(&chunk_position)?.get_block_entity(x, y, z)?;
+        self.get_chunk_mut(chunk_position).and_then(|chunk| chunk.remove_block_entity(x, y, z))
+    }
+
+    // none until the chunk has been lit
+    pub fn get_chunk_light(&self, position: ChunkPosition) -> Option<&ChunkLight> {
+        self.light.get(&position)
+    }
+
+    pub fn set_chunk_light(&mut self, position: ChunkPosition, light: ChunkLight) {
+        if self.chunks.contains_key(&position) {
+            self.light.insert(position, light);
+        }
+    }
+
+    pub fn get_light(&self, position: BlockPosition, channel: LightChannel) -> Option<u8> {
+        let (x, y, z) = Self::local_position(position);
+        self.get_chunk_light(Self::chunk_position(position)).map(|light| light.get(channel, x, y, z))
+    }
+
+    // returns whether the level changed, the caller marks the chunks for remeshing
+    pub fn set_light(&mut self, position: BlockPosition, channel: LightChannel, level: u8) -> bool {
+        let (x, y, z) = Self::local_position(position);
+        let Some(light) = self.light.get_mut(&Self::chunk_position(position)) else {
+            return false;
+        };
+        if light.get(channel, x, y, z) == level {
+            return false;
+        }
+        light.set(channel, x, y, z, level);
+        true
+    }
+
+    // looks a block up relative to a chunk, the offset may leave the chunk
+    pub fn get_block_relative(&self, chunk_position: ChunkPosition, x: i64, y: i64, z: i64) -> Option<Block> {
+        let origin = Self::world_position(chunk_position, (0, 0, 0));
+        self.get_block((origin.0 + x, origin.1 + y, origin.2 + z))
+    }
+}
+impl Default for ChunkMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,236 @@
+use std::io::BufRead;
+use std::sync::mpsc::{self, Receiver};
+
+use crate::world::assets::save_json_file;
+use crate::world::chunk_map::BlockPosition;
+use crate::world::edit_journal::PlayerId;
+use crate::world::objects::chunk::Block;
+use crate::world::world::World;
+use crate::world::time::parse_time_of_day;
+use crate::world::weather::{ForcedWeather, Precipitation, WeatherKind};
+use crate::config::{EDIT_VOLUME_LIMIT, WEATHER_LENGTH};
+use crate::world::structures::{export_structure, ExportOptions};
+use crate::world::world_edit::{self, Transform};
+
+// who runs a command and where they stand
+pub struct CommandContext {
+    pub player: PlayerId,
+    pub position: BlockPosition
+}
+
+// runs a command like "/fill stone", the ok message or the error is meant for the player
+pub fn execute_command(world: &mut World, context: &CommandContext, command: &str) -> Result<String, String> {
+    let mut arguments = command.trim().trim_start_matches('/').split_whitespace();
+    let Some(name) = arguments.next() else {
+        return Err("No command given".to_string());
+    };
+    let arguments: Vec<&str> = arguments.collect();
+    let player = context.player;
+
+    match name {
+        "pos1" | "pos2" => {
+            let position = if arguments.is_empty() { context.position } else { parse_position(&arguments)? };
+            let selection = &mut world.get_edit_session(player).selection;
+            if name == "pos1" {
+                selection.first = Some(position);
+            } else {
+                selection.second = Some(position);
+            }
+            Ok(format!("Set {} to {:?}", name, position))
+        }
+        "fill" | "hollow" => {
+            let region = selected_region(world, player)?;
+            let block = parse_block(world, arguments.first())?;
+            let changed = if name == "fill" {
+                world_edit::fill(world, player, region, block)
+            } else {
+                world_edit::hollow(world, player, region, block)
+            };
+            Ok(format!("Changed {} blocks", changed))
+        }
+        "replace" => {
+            let region = selected_region(world, player)?;
+            let from = parse_block(world, arguments.first())?;
+            let to = parse_block(world, arguments.get(1))?;
+            let changed = world_edit::replace(world, player, region, from.id, to);
+            Ok(format!("Replaced {} blocks", changed))
+        }
+        "copy" => {
+            let region = selected_region(world, player)?;
+            let clipboard = world_edit::copy(world, region, context.position);
+            let copied = clipboard.len();
+            world.get_edit_session(player).clipboard = Some(clipboard);
+            Ok(format!("Copied {} blocks", copied))
+        }
+        // paste [rotate 90|180|270] [mirror x|z] [air]
+        "paste" => {
+            let Some(clipboard) = world.get_edit_session(player).clipboard.clone() else {
+                return Err("The clipboard is empty, copy something first".to_string());
+            };
+            let (transform, include_air) = parse_paste_options(&arguments)?;
+            let changed = world_edit::paste(world, player, &clipboard, context.position, &transform, include_air);
+            Ok(format!("Pasted {} blocks", changed))
+        }
+        // export <name> [air] [states] [origin], origin makes where the player stands 0, 0, 0
+        "export" => {
+            let region = selected_region(world, player)?;
+            let name = arguments.first().ok_or("Missing a structure name")?;
+            // the name becomes a file name, so it can't point outside the structures folder
+            if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                return Err(format!("Structure names can only use letters, digits, _ and -, not {}", name));
+            }
+            let mut options = ExportOptions::default();
+            for argument in &arguments[1..] {
+                match *argument {
+                    "air" => options.include_air = true,
+                    "states" => options.include_states = true,
+                    "origin" => options.origin = Some(context.position),
+                    _ => return Err(format!("Unknown export option {}", argument))
+                }
+            }
+            let structure = export_structure(world, name, region, &options);
+            let content = structure.to_def(world.get_blocks(), options.include_states).to_json();
+            let path = save_json_file("structures", &format!("{}.json", name), &content)
+                .map_err(|err| format!("Failed to save the structure {}: {}", name, err))?;
+            let exported = structure.blocks.len();
+            world.get_structures_mut().add(structure);
+            Ok(format!("Exported {} blocks to {}", exported, path.display()))
+        }
+        // import <name> [rotate 90|180|270] [mirror x|z] [air], placed with its origin where the player stands
+        "import" => {
+            let name = arguments.first().ok_or("Missing a structure name")?;
+            let structure = world.get_structures().get(name).ok_or_else(|| format!("Unknown structure {}", name))?;
+            let clipboard = structure.to_clipboard();
+            let (transform, include_air) = parse_paste_options(&arguments[1..])?;
+            let changed = world_edit::paste(world, player, &clipboard, context.position, &transform, include_air);
+            Ok(format!("Placed {} blocks", changed))
+        }
+        "move" => {
+            let region = selected_region(world, player)?;
+            let offset = parse_position(&arguments)?;
+            let changed = world_edit::move_region(world, player, region, offset);
+            Ok(format!("Moved {} blocks", changed))
+        }
+        "undo" => match world.undo(player) {
+            true => Ok("Undid the last edit".to_string()),
+            false => Err("Nothing to undo".to_string())
+        },
+        "redo" => match world.redo(player) {
+            true => Ok("Redid the last edit".to_string()),
+            false => Err("Nothing to redo".to_string())
+        },
+        // time [set <noon|midnight|...|ticks>|add <ticks>|freeze|unfreeze]
+        "time" => {
+            let time = world.get_time_mut();
+            match arguments.first().copied() {
+                None => {}
+                Some("set") => {
+                    let argument = arguments.get(1).ok_or("Missing a time of day")?;
+                    let fraction = parse_time_of_day(argument, time.day_length).ok_or_else(|| format!("Unknown time of day {}", argument))?;
+                    time.set_time_of_day(fraction);
+                }
+                Some("add") => {
+                    let ticks: u64 = arguments.get(1).and_then(|ticks| ticks.parse().ok()).ok_or("add needs a number of ticks")?;
+                    time.ticks = time.ticks.saturating_add(ticks);
+                }
+                Some("freeze") => time.frozen = true,
+                Some("unfreeze") => time.frozen = false,
+                Some(argument) => return Err(format!("Unknown time option {}", argument))
+            }
+            let day_ticks = time.ticks % time.day_length.max(1);
+            let frozen = if time.frozen { ", frozen" } else { "" };
+            Ok(format!("Day {}, {} of {} ticks{}", time.day(), day_ticks, time.day_length, frozen))
+        }
+        // weather [clear|rain|thunder [ticks]|auto]
+        "weather" => {
+            let now = world.get_time().ticks;
+            match arguments.first().copied() {
+                None => {}
+                Some("auto") => world.get_weather_settings_mut().forced = None,
+                Some(argument) => {
+                    let kind = WeatherKind::parse(argument).ok_or_else(|| format!("Unknown weather {}", argument))?;
+                    let ticks = match arguments.get(1) {
+                        Some(ticks) => ticks.parse::<u64>().map_err(|_| format!("Not a number of ticks {}", ticks))?,
+                        None => WEATHER_LENGTH
+                    };
+                    world.get_weather_settings_mut().forced = Some(ForcedWeather { kind, from: now, until: now.saturating_add(ticks) });
+                }
+            }
+            let weather = world.weather_at(context.position);
+            let falling = match world.precipitation_at(context.position) {
+                Precipitation::None => "",
+                Precipitation::Rain => ", raining here",
+                Precipitation::Snow => ", snowing here"
+            };
+            let forced = match world.get_weather_settings().forced.filter(|forced| forced.until > now) {
+                Some(forced) => format!(", {} for {} more ticks", forced.kind.name(), forced.until - now),
+                None => String::new()
+            };
+            Ok(format!("Weather is {}{}{}", weather.kind().name(), falling, forced))
+        }
+        _ => Err(format!("Unknown command {}", name))
+    }
+}
+
+// commands typed into the terminal the game was started from, one per line
+pub fn spawn_console() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break; };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+// rotate 90|180|270, mirror x|z and air, in any order
+fn parse_paste_options(arguments: &[&str]) -> Result<(Transform, bool), String> {
+    let mut transform = Transform::default();
+    let mut include_air = false;
+    let mut arguments = arguments.iter();
+    while let Some(argument) = arguments.next() {
+        match *argument {
+            "rotate" => {
+                let degrees: i32 = arguments.next().and_then(|degrees| degrees.parse().ok())
+                    .filter(|degrees| degrees % 90 == 0)
+                    .ok_or("rotate needs a multiple of 90 degrees")?;
+                transform.quarter_turns = degrees / 90;
+            }
+            "mirror" => match arguments.next() {
+                Some(&"x") => transform.mirror_x = true,
+                Some(&"z") => transform.mirror_z = true,
+                _ => return Err("mirror needs x or z".to_string())
+            },
+            "air" => include_air = true,
+            _ => return Err(format!("Unknown option {}", argument))
+        }
+    }
+    Ok((transform, include_air))
+}
+
+// every command on the selection visits each block in it, so huge selections are refused
+fn selected_region(world: &mut World, player: PlayerId) -> Result<world_edit::Region, String> {
+    let region = world.get_edit_session(player).selection.region()
+        .ok_or_else(|| "Select a region with pos1 and pos2 first".to_string())?;
+    if region.volume() > EDIT_VOLUME_LIMIT {
+        return Err(format!("The selection has {} blocks, at most {} can be edited at once", region.volume(), EDIT_VOLUME_LIMIT));
+    }
+    Ok(region)
+}
+
+fn parse_block(world: &World, argument: Option<&&str>) -> Result<Block, String> {
+    let argument = argument.ok_or("Missing a block")?;
+    world.get_blocks().parse_block(argument).ok_or_else(|| format!("Unknown block {}", argument))
+}
+
+fn parse_position(arguments: &[&str]) -> Result<BlockPosition, String> {
+    let values: Vec<i64> = arguments.iter().take(3).map(|value| value.parse::<i64>()).collect::<Result<_, _>>()
+        .map_err(|_| "Positions are three whole numbers".to_string())?;
+    match values[..] {
+        [x, y, z] => Ok((x, y, z)),
+        _ => Err("Positions are three whole numbers".to_string())
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::world::block_entities::BlockEntity;
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition};
+use crate::world::objects::chunk::Block;
+
+pub type PlayerId = u32;
+
+// the player sitting in front of this game
+pub const LOCAL_PLAYER: PlayerId = 0;
+
+// one block that changed, block entities are kept so undoing the break of a
+// chest brings its contents back
+pub struct BlockChange {
+    pub position: BlockPosition,
+    pub before: Block,
+    pub after: Block,
+    pub before_entity: Option<Box<dyn BlockEntity>>,
+    pub after_entity: Option<Box<dyn BlockEntity>>
+}
+
+// changes that are undone and redone together, a block that changes more
+// than once only keeps where it started and where it ended
+#[derive(Default)]
+pub struct Transaction {
+    changes: Vec<BlockChange>,
+    indices: HashMap<BlockPosition, usize>
+}
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, change: BlockChange) {
+        match self.indices.get(&change.position) {
+            Some(index) => {
+                let existing = &mut self.changes[*index];
+                existing.after = change.after;
+                existing.after_entity = change.after_entity;
+            }
+            None => {
+                self.indices.insert(change.position, self.changes.len());
+                self.changes.push(change);
+            }
+        }
+    }
+
+    // changes that ended where they started are left out
+    pub fn changes(&self) -> impl DoubleEndedIterator<Item = &BlockChange> {
+        self.changes.iter().filter(|change| change.before != change.after || change.before_entity.is_some() || change.after_entity.is_some())
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes().next().is_none()
+    }
+
+    pub fn affected_chunks(&self) -> HashSet<ChunkPosition> {
+        self.changes().map(|change| ChunkMap::chunk_position(change.position)).collect()
+    }
+}
+
+#[derive(Default)]
+struct EditHistory {
+    undo: VecDeque<Transaction>,
+    redo: Vec<Transaction>,
+    open: Option<Transaction>,
+    // begin can be called inside another transaction, only the outer commit counts
+    depth: u32
+}
+
+// every player's undo and redo history, at most history_length transactions
+// are kept per player and the oldest ones are forgotten first
+pub struct EditJournal {
+    histories: HashMap<PlayerId, EditHistory>,
+    history_length: usize
+}
+impl EditJournal {
+    pub fn new(history_length: usize) -> Self {
+        Self {
+            histories: HashMap::new(),
+            history_length
+        }
+    }
+
+    pub fn begin(&mut self, player: PlayerId) {
+        let history = self.histories.entry(player).or_default();
+        if history.depth == 0 {
+            history.open = Some(Transaction::new());
+        }
+        history.depth += 1;
+    }
+
+    pub fn commit(&mut self, player: PlayerId) {
+        let Some(history) = self.histories.get_mut(&player) else {
+            return;
+        };
+        if history.depth == 0 {
+            return;
+        }
+        history.depth -= 1;
+        if history.depth == 0 {
+            if let Some(transaction) = history.open.take() {
+                self.push_undo(player, transaction);
+            }
+        }
+    }
+
+    pub fn is_open(&self, player: PlayerId) -> bool {
+        self.histories.get(&player).is_some_and(|history| history.open.is_some())
+    }
+
+    // a change outside of begin and commit is a transaction of its own
+    pub fn record(&mut self, player: PlayerId, change: BlockChange) {
+        let history = self.histories.entry(player).or_default();
+        match history.open.as_mut() {
+            Some(transaction) => transaction.record(change),
+            None => {
+                let mut transaction = Transaction::new();
+                transaction.record(change);
+                self.push_undo(player, transaction);
+            }
+        }
+    }
+
+    // a new edit makes the redo history meaningless
+    fn push_undo(&mut self, player: PlayerId, transaction: Transaction) {
+        if transaction.is_empty() {
+            return;
+        }
+        let history = self.histories.entry(player).or_default();
+        history.redo.clear();
+        history.undo.push_back(transaction);
+        while history.undo.len() > self.history_length {
+            history.undo.pop_front();
+        }
+    }
+
+    pub fn take_undo(&mut self, player: PlayerId) -> Option<Transaction> {
+        self.histories.get_mut(&player)?.undo.pop_back()
+    }
+
+    pub fn take_redo(&mut self, player: PlayerId) -> Option<Transaction> {
+        self.histories.get_mut(&player)?.redo.pop()
+    }
+
+    // undone transactions go here so they can be redone
+    pub fn push_redo(&mut self, player: PlayerId, transaction: Transaction) {
+        self.histories.entry(player).or_default().redo.push(transaction);
+    }
+
+    // redone transactions go back without clearing the rest of the redo history
+    pub fn push_redone(&mut self, player: PlayerId, transaction: Transaction) {
+        let history = self.histories.entry(player).or_default();
+        history.undo.push_back(transaction);
+        while history.undo.len() > self.history_length {
+            history.undo.pop_front();
+        }
+    }
+
+    pub fn undo_len(&self, player: PlayerId) -> usize {
+        self.histories.get(&player).map_or(0, |history| history.undo.len())
+    }
+
+    pub fn redo_len(&self, player: PlayerId) -> usize {
+        self.histories.get(&player).map_or(0, |history| history.redo.len())
+    }
+
+    pub fn clear(&mut self, player: PlayerId) {
+        self.histories.remove(&player);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::world::block_updates::can_fall_into;
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition};
+use crate::world::objects::chunk::Block;
+use crate::world::world::World;
+
+// in blocks per tick, the fall speed is capped below a block per tick so
+// a falling block never skips over the block it should land on
+const GRAVITY: f64 = 0.04;
+const MAX_FALL_SPEED: f64 = 1.0;
+
+// a block that left the grid to fall, like sand with nothing below it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FallingBlock {
+    pub block: Block,
+    // the center of the block, in block units
+    pub position: (f64, f64, f64),
+    // how far it falls per tick
+    pub velocity: f64
+}
+impl FallingBlock {
+    pub fn new(block: Block, position: BlockPosition) -> Self {
+        Self {
+            block,
+            position: (position.0 as f64, position.1 as f64, position.2 as f64),
+            velocity: 0.0
+        }
+    }
+
+    // the lowest block it is in
+    pub fn block_position(&self) -> BlockPosition {
+        (self.position.0.round() as i64, self.position.1.floor() as i64, self.position.2.round() as i64)
+    }
+}
+
+// moves every falling block by one tick, the ones that hit something are
+// placed back into the world on top of it
+pub fn update_falling_blocks(world: &mut World) {
+    for mut falling in world.take_falling_blocks() {
+        match fall(world, &mut falling) {
+            Some(landed) => land(world, falling.block, landed),
+            None => world.add_falling_block(falling)
+        }
+    }
+}
+
+// where it landed, none while it is still falling. a block that would fall
+// into a chunk that isn't loaded waits above it
+fn fall(world: &World, falling: &mut FallingBlock) -> Option<BlockPosition> {
+    let (x, _, z) = falling.block_position();
+    let velocity = (falling.velocity + GRAVITY).min(MAX_FALL_SPEED);
+    let y = falling.position.1 - velocity;
+
+    // the blocks it enters this tick, from the top down
+    let mut lowest = falling.position.1.floor() as i64;
+    while lowest > y.floor() as i64 {
+        match world.get_block((x, lowest - 1, z)) {
+            None => {
+                falling.position.1 = lowest as f64;
+                falling.velocity = 0.0;
+                return None;
+            }
+            Some(below) if !can_fall_into(world.get_blocks(), below) => return Some((x, lowest, z)),
+            _ => lowest -= 1
+        }
+    }
+    falling.position.1 = y;
+    falling.velocity = velocity;
+    None
+}
+
+// something may have been built where it lands while it was falling, then it breaks
+fn land(world: &mut World, block: Block, position: BlockPosition) {
+    if world.get_block(position).is_some_and(|current| can_fall_into(world.get_blocks(), current)) {
+        world.set_block(position, block);
+    }
+}
+
+// falling blocks in a chunk that is about to be unloaded are placed where
+// they are, they fall on once the chunk is back
+pub fn land_in_chunk(world: &mut World, chunk_position: ChunkPosition) {
+    for falling in world.take_falling_blocks() {
+        let position = ChunkMap::block_at_point(falling.position.0 as f32, falling.position.1 as f32, falling.position.2 as f32);
+        if ChunkMap::chunk_position(position) == chunk_position {
+            land(world, falling.block, position);
+        } else {
+            world.add_falling_block(falling);
+        }
+    }
+}
+
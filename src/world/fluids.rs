@@ -0,0 +1,274 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::world::blocks::BlockRegistry;
+use crate::world::chunk_map::{BlockPosition, ChunkMap};
+use crate::world::objects::chunk::{Block, BlockId, AIR};
+use crate::world::world::World;
+
+// fluids are blocks with a "fluid" section in their model file. how far a
+// block is from its source is the "level" state, 0 is the source itself, and
+// blocks pouring down from above have "falling" set
+pub const LEVEL_STATE: &str = "level";
+pub const FALLING_STATE: &str = "falling";
+
+const HORIZONTAL_OFFSETS: [(i64, i64, i64); 4] = [(1, 0, 0), (-1, 0, 0), (0, 0, 1), (0, 0, -1)];
+
+// how a fluid block flows and how things move through it
+#[derive(Deserialize, Clone, Debug)]
+pub struct FluidDef {
+    // how many blocks it flows away from a source, at most the highest level state
+    pub flow_distance: u8,
+    // ticks between two flow steps
+    pub tick_rate: u64,
+    // whether a flowing block between two sources becomes a source too
+    #[serde(default)]
+    pub infinite: bool,
+    // how hard it pushes things up and slows them down, for the physics
+    #[serde(default)]
+    pub buoyancy: f32,
+    #[serde(default)]
+    pub drag: f32,
+    #[serde(default)]
+    pub interactions: Vec<FluidInteraction>
+}
+
+// what a fluid turns into when it touches another fluid, like lava
+// turning into stone next to water
+#[derive(Deserialize, Clone, Debug)]
+pub struct FluidInteraction {
+    pub fluid: String,
+    pub source: String,
+    pub flowing: String
+}
+
+// the level and whether it is falling, for any fluid block
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FluidState {
+    pub level: u8,
+    pub falling: bool
+}
+impl FluidState {
+    pub const SOURCE: FluidState = FluidState { level: 0, falling: false };
+
+    pub fn is_source(&self) -> bool {
+        *self == Self::SOURCE
+    }
+
+    // falling fluid spreads like a source once it lands
+    fn spread_level(&self) -> u8 {
+        if self.falling { 0 } else { self.level }
+    }
+}
+
+pub fn fluid_state(blocks: &BlockRegistry, block: Block) -> FluidState {
+    let Some(definition) = blocks.get(block.id) else {
+        return FluidState::SOURCE;
+    };
+    FluidState {
+        level: definition.get_state_value(block.state, LEVEL_STATE).and_then(|level| level.parse().ok()).unwrap_or(0),
+        falling: definition.get_state_value(block.state, FALLING_STATE) == Some("true")
+    }
+}
+
+pub fn fluid_block(blocks: &BlockRegistry, id: BlockId, state: FluidState) -> Block {
+    let Some(definition) = blocks.get(id) else {
+        return Block::from_id(id);
+    };
+    let block_state = definition.with_state_value(0, LEVEL_STATE, &state.level.to_string()).unwrap_or(0);
+    let block_state = definition.with_state_value(block_state, FALLING_STATE, if state.falling { "true" } else { "false" }).unwrap_or(block_state);
+    Block::new(id, block_state)
+}
+
+// fluid blocks waiting for their next flow step, by the tick they are due
+#[derive(Clone, Default)]
+pub struct FluidTicks {
+    scheduled: BTreeMap<u64, Vec<BlockPosition>>,
+    positions: HashSet<BlockPosition>
+}
+impl FluidTicks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // a block that is already waiting keeps its earlier tick
+    pub fn schedule(&mut self, position: BlockPosition, tick: u64) {
+        if self.positions.insert(position) {
+            self.scheduled.entry(tick).or_default().push(position);
+        }
+    }
+
+    // at most limit blocks that are due, the rest stay for the next tick
+    pub fn take_due(&mut self, tick: u64, limit: usize) -> Vec<BlockPosition> {
+        let mut due = Vec::new();
+        while due.len() < limit {
+            let Some(mut entry) = self.scheduled.first_entry() else { break; };
+            if *entry.key() > tick {
+                break;
+            }
+            let positions = entry.get_mut();
+            let count = positions.len().min(limit - due.len());
+            due.extend(positions.drain(..count));
+            if positions.is_empty() {
+                entry.remove();
+            }
+        }
+        for position in &due {
+            self.positions.remove(position);
+        }
+        due
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+// one flow step of the fluid block at the position. changing a block
+// schedules the fluids around it, so flowing keeps going until it settles
+pub fn update_fluid(world: &mut World, position: BlockPosition) {
+    let Some(block) = world.get_block(position) else {
+        return;
+    };
+    let Some(fluid) = world.get_blocks().fluid(block).cloned() else {
+        return;
+    };
+    let state = fluid_state(world.get_blocks(), block);
+
+    if let Some(hardened) = interaction(world, position, &fluid, state) {
+        world.set_block(position, hardened);
+        return;
+    }
+
+    // sources stay, flowing blocks follow what feeds them
+    if !state.is_source() {
+        match flowing_state(world, position, block.id, &fluid) {
+            None => {
+                world.set_block(position, AIR);
+                return;
+            }
+            Some(new_state) if new_state != state => {
+                let new_block = fluid_block(world.get_blocks(), block.id, new_state);
+                world.set_block(position, new_block);
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    spread(world, position, block.id, &fluid, state);
+}
+
+// the first fluid this one touches that it reacts with decides what it becomes
+fn interaction(world: &World, position: BlockPosition, fluid: &FluidDef, state: FluidState) -> Option<Block> {
+    let blocks = world.get_blocks();
+    for offset in HORIZONTAL_OFFSETS.iter().chain(&[(0, 1, 0), (0, -1, 0)]) {
+        let Some(neighbour) = world.get_block((position.0 + offset.0, position.1 + offset.1, position.2 + offset.2)) else {
+            continue;
+        };
+        let Some(neighbour_name) = blocks.get(neighbour.id).map(|definition| definition.name.as_str()) else {
+            continue;
+        };
+        if let Some(interaction) = fluid.interactions.iter().find(|interaction| interaction.fluid == neighbour_name) {
+            let result = if state.is_source() { &interaction.source } else { &interaction.flowing };
+            return blocks.parse_block(result);
+        }
+    }
+    None
+}
+
+// none when nothing feeds the block anymore
+fn flowing_state(world: &World, position: BlockPosition, id: BlockId, fluid: &FluidDef) -> Option<FluidState> {
+    let blocks = world.get_blocks();
+    let same_fluid = |position: BlockPosition| world.get_block(position).filter(|block| block.id == id);
+
+    if same_fluid((position.0, position.1 + 1, position.2)).is_some() {
+        return Some(FluidState { level: 1, falling: true });
+    }
+
+    let neighbours: Vec<FluidState> = HORIZONTAL_OFFSETS.iter()
+        .filter_map(|offset| same_fluid((position.0 + offset.0, position.1, position.2 + offset.2)))
+        .map(|block| fluid_state(blocks, block))
+        .collect();
+    if fluid.infinite && neighbours.iter().filter(|neighbour| neighbour.is_source()).count() >= 2 {
+        let below = world.get_block((position.0, position.1 - 1, position.2));
+        let supported = below.is_some_and(|below| {
+            blocks.collides(below) || (below.id == id && fluid_state(blocks, below).is_source())
+        });
+        if supported {
+            return Some(FluidState::SOURCE);
+        }
+    }
+
+    let level = neighbours.iter().map(|neighbour| neighbour.spread_level()).min()? + 1;
+    (level <= fluid.flow_distance).then_some(FluidState { level, falling: false })
+}
+
+// down first, sideways only when it can't fall or it is a source
+fn spread(world: &mut World, position: BlockPosition, id: BlockId, fluid: &FluidDef, state: FluidState) {
+    let below = (position.0, position.1 - 1, position.2);
+    let falling = FluidState { level: 1, falling: true };
+    if let Some(below_block) = world.get_block(below) {
+        if can_flow_into(world.get_blocks(), below_block, id, falling) {
+            let block = fluid_block(world.get_blocks(), id, falling);
+            world.set_block(below, block);
+            if !state.is_source() {
+                return;
+            }
+        } else if below_block.id == id && !state.is_source() {
+            return;
+        }
+    }
+
+    let level = state.spread_level() + 1;
+    if level > fluid.flow_distance {
+        return;
+    }
+    let flowing = FluidState { level, falling: false };
+    for offset in HORIZONTAL_OFFSETS {
+        let neighbour = (position.0 + offset.0, position.1, position.2 + offset.2);
+        let Some(neighbour_block) = world.get_block(neighbour) else {
+            continue;
+        };
+        if can_flow_into(world.get_blocks(), neighbour_block, id, flowing) {
+            let block = fluid_block(world.get_blocks(), id, flowing);
+            world.set_block(neighbour, block);
+        }
+    }
+}
+
+// air and things like plants get washed away, flowing blocks of the same
+// fluid are replaced when this one would be closer to a source
+fn can_flow_into(blocks: &BlockRegistry, block: Block, id: BlockId, state: FluidState) -> bool {
+    if block.id == id {
+        let current = fluid_state(blocks, block);
+        return !current.is_source() && !current.falling && (state.falling || state.level < current.level);
+    }
+    blocks.fluid(block).is_none() && (block.is_air() || !blocks.collides(block)) && blocks.get(block.id).is_none_or(|definition| definition.block_entity.is_none())
+}
+
+// how far up a fluid block is filled from 0 to 1, sources and falling
+// fluid fill the whole block and flowing fluid gets lower with its level
+pub fn fluid_height(blocks: &BlockRegistry, block: Block) -> f32 {
+    let Some(fluid) = blocks.fluid(block) else {
+        return 1.0;
+    };
+    let state = fluid_state(blocks, block);
+    if state.falling { 1.0 } else { 1.0 - state.level as f32 / (fluid.flow_distance as f32 + 1.0) }
+}
+
+// the fluid a point in block units is in, for swimming and buoyancy. a
+// flowing block is only filled up to its height
+pub fn fluid_at(world: &World, x: f32, y: f32, z: f32) -> Option<&FluidDef> {
+    let position = ChunkMap::block_at_point(x, y, z);
+    let block = world.get_block(position)?;
+    let fluid = world.get_blocks().fluid(block)?;
+    let height = fluid_height(world.get_blocks(), block);
+    // blocks span -0.5 to 0.5 around their position
+    (y - position.1 as f32 + 0.5 <= height).then_some(fluid)
+}
@@ -0,0 +1,70 @@
+use noise::NoiseFn;
+
+use crate::config::{BIOME_BLEND_COUNT, BIOME_BLEND_DISTANCE, SEA_LEVEL};
+use crate::world::generation::randomness::RandomnessFunctions;
+
+// climate changes over thousands of blocks, much slower than the terrain
+const TEMPERATURE_SCALE: f64 = 2000.0;
+const MOISTURE_SCALE: f64 = 1000.0;
+// moisture samples the noise far away from temperature so the two don't follow each other
+const MOISTURE_OFFSET: f64 = 5000.5;
+// it gets colder going up a mountain, in degrees per block above the sea
+const LAPSE_RATE: f32 = 0.3;
+
+// temperature in degrees and moisture in percent, on the same scale the
+// biome files use
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Climate {
+    pub temperature: f32,
+    pub moisture: f32
+}
+impl Climate {
+    // the temperature at a height, the climate itself is the one at sea level
+    pub fn temperature_at(&self, y: i64) -> f32 {
+        self.temperature - (y - SEA_LEVEL).max(0) as f32 * LAPSE_RATE
+    }
+
+    // how far a biome's temperature and moisture are from this climate
+    pub fn distance(&self, temperature: f32, moisture: f32) -> f32 {
+        ((self.temperature - temperature).powi(2) + (self.moisture - moisture).powi(2)).sqrt()
+    }
+}
+
+// the climate of a column, biomes and weather both follow it
+pub fn climate_at(x: i64, z: i64, randomness_functions: &RandomnessFunctions) -> Climate {
+    let noise = &randomness_functions.noise;
+    let temperature = noise.get([x as f64 / TEMPERATURE_SCALE, z as f64 / TEMPERATURE_SCALE]) * 30.0 + 10.0;
+    let moisture = noise.get([x as f64 / MOISTURE_SCALE + MOISTURE_OFFSET, z as f64 / MOISTURE_SCALE + MOISTURE_OFFSET]) * 50.0 + 40.0;
+    Climate {
+        temperature: temperature as f32,
+        moisture: moisture.clamp(0.0, 100.0) as f32
+    }
+}
+
+// the biomes, by index, that a climate mixes with how much each counts, the
+// weights add up to 1 and the heaviest comes first. a biome counts less the
+// further it is behind the closest one and not at all past the blend
+// distance, so the weights change smoothly when the climate does
+pub fn blend_weights(climate: Climate, biomes: &[(f32, f32)]) -> Vec<(usize, f32)> {
+    let mut distances: Vec<(usize, f32)> = biomes.iter().enumerate()
+        .map(|(index, (temperature, moisture))| (index, climate.distance(*temperature, *moisture)))
+        .collect();
+    distances.sort_by(|first, second| first.1.total_cmp(&second.1));
+    distances.truncate(BIOME_BLEND_COUNT);
+    let Some(&(_, closest)) = distances.first() else {
+        return Vec::new();
+    };
+
+    let mut weights: Vec<(usize, f32)> = distances.into_iter()
+        .map(|(index, distance)| {
+            let amount = (1.0 - (distance - closest) / BIOME_BLEND_DISTANCE).clamp(0.0, 1.0);
+            (index, amount * amount * (3.0 - 2.0 * amount))
+        })
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect();
+    let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+    for (_, weight) in &mut weights {
+        *weight /= total;
+    }
+    weights
+}
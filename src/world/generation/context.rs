@@ -0,0 +1,150 @@
+use crate::world::biomes::{Biome, Biomes};
+use crate::world::blocks::BlockRegistry;
+use crate::world::generation::climate::{climate_at, Climate};
+use crate::world::generation::oceans::Oceans;
+use crate::world::generation::randomness::RandomnessFunctions;
+use crate::world::generation::terrain::Terrain;
+use crate::world::objects::chunk::Block;
+use crate::world::structures::Structures;
+use crate::world::world::World;
+
+// the layers worlds without any biome get, grass on dirt on stone
+const FALLBACK_LEVELS: [(&str, u32); 3] = [("grass_1", 0), ("dirt", 1), ("stone", 4)];
+// keeps the rolls for surfaces apart from the other position hashes
+const SURFACE_SALT: i64 = 0x53555246;
+
+// a biome the way the generator uses it, with its block names looked up
+#[derive(Clone, Debug)]
+pub struct GenerationBiome {
+    pub name: String,
+    pub temperature: f32,
+    pub moisture: f32,
+    // from the surface down, every layer starts depth blocks below the surface
+    pub layers: Vec<(Vec<Block>, i64)>,
+    // structure names with their chance, every list may place one per surface block
+    pub trees: Vec<(String, f32)>,
+    pub folliage: Vec<(String, f32)>,
+    pub buildings: Vec<(String, f32)>
+}
+impl GenerationBiome {
+    // block names that aren't loaded are left out, and so are layers without any block
+    pub fn new(biome: &Biome, blocks: &BlockRegistry) -> Self {
+        let mut layers: Vec<(Vec<Block>, i64)> = biome.block_levels.iter()
+            .map(|(names, depth)| (names.iter().filter_map(|name| blocks.parse_block(name)).collect::<Vec<Block>>(), *depth as i64))
+            .filter(|(layer_blocks, _)| !layer_blocks.is_empty())
+            .collect();
+        layers.sort_by_key(|(_, depth)| *depth);
+
+        Self {
+            name: biome.name.clone(),
+            temperature: biome.temperature,
+            moisture: biome.moisture,
+            layers,
+            trees: biome.trees.clone(),
+            folliage: biome.folliage.clone(),
+            buildings: biome.buildings.clone()
+        }
+    }
+
+    pub fn fallback(blocks: &BlockRegistry) -> Self {
+        Self {
+            name: "fallback".to_string(),
+            temperature: 0.0,
+            moisture: 0.0,
+            layers: FALLBACK_LEVELS.iter()
+                .filter_map(|(name, depth)| Some((vec![blocks.parse_block(name)?], *depth as i64)))
+                .collect(),
+            trees: Vec::new(),
+            folliage: Vec::new(),
+            buildings: Vec::new()
+        }
+    }
+
+    // the layer a block this deep below the surface is in, none above the surface
+    pub fn layer_at(&self, depth: i64) -> Option<&[Block]> {
+        self.layers.iter().rev()
+            .find(|(_, start)| depth >= *start)
+            .map(|(layer_blocks, _)| layer_blocks.as_slice())
+    }
+}
+
+// everything the generator reads, a copy of it is shared by every generator
+// thread so chunks generate without touching the world
+pub struct GenerationContext {
+    pub randomness_functions: RandomnessFunctions,
+    pub structures: Structures,
+    pub oceans: Oceans,
+    pub terrain: Terrain,
+    // the block every biome ends in and how deep below the surface all of
+    // them have reached it, none when their deepest layers differ
+    pub deep_layer: Option<(Block, i64)>,
+    // in the same order as the biomes of the terrain
+    biomes: Vec<GenerationBiome>
+}
+impl GenerationContext {
+    pub fn new(world: &World) -> Self {
+        Self::from_parts(world.get_randomness_functions().clone(), world.get_biomes(), world.get_blocks(), world.get_structures().clone())
+    }
+
+    pub fn from_parts(randomness_functions: RandomnessFunctions, biomes: &Biomes, blocks: &BlockRegistry, structures: Structures) -> Self {
+        let mut generation_biomes: Vec<GenerationBiome> = biomes.iter().map(|biome| GenerationBiome::new(biome, blocks)).collect();
+        if generation_biomes.is_empty() {
+            generation_biomes.push(GenerationBiome::fallback(blocks));
+        }
+
+        Self {
+            randomness_functions,
+            structures,
+            oceans: Oceans::new(blocks),
+            terrain: Terrain::new(biomes),
+            deep_layer: deep_layer(&generation_biomes),
+            biomes: generation_biomes
+        }
+    }
+
+    pub fn climate_at(&self, x: i64, z: i64) -> Climate {
+        climate_at(x, z, &self.randomness_functions)
+    }
+
+    // the biome whose temperature and moisture are closest to the climate
+    pub fn closest_biome(&self, climate: Climate) -> &GenerationBiome {
+        let distance = |biome: &GenerationBiome| climate.distance(biome.temperature, biome.moisture);
+        self.biomes.iter()
+            .min_by(|first, second| distance(first).total_cmp(&distance(second)))
+            .expect("There is always at least the fallback biome")
+    }
+
+    // the biome whose layers and trees a column gets. where biomes mix one of
+    // them is picked at random by its weight, so their surfaces fray into
+    // each other instead of meeting at a line
+    pub fn surface_biome(&self, x: i64, z: i64) -> &GenerationBiome {
+        let weights = self.terrain.blend(x, z, &self.randomness_functions);
+        let hash = self.randomness_functions.position_hash(x, SURFACE_SALT, z);
+        let mut roll = (hash >> 40) as f32 / (1u64 << 24) as f32;
+        for (index, weight) in &weights {
+            if roll < *weight {
+                return &self.biomes[*index];
+            }
+            roll -= weight;
+        }
+        // rounding can leave a sliver at the end, and worlds without biomes have no weights
+        weights.first().map_or(&self.biomes[0], |(index, _)| &self.biomes[*index])
+    }
+}
+
+// the deepest layer of every biome has to be the same single block
+fn deep_layer(biomes: &[GenerationBiome]) -> Option<(Block, i64)> {
+    let mut deep_layer: Option<(Block, i64)> = None;
+    for biome in biomes {
+        let (layer_blocks, depth) = biome.layers.last()?;
+        let [block] = layer_blocks.as_slice() else {
+            return None;
+        };
+        match deep_layer {
+            Some((deep_block, _)) if deep_block != *block => return None,
+            Some((_, deep_depth)) => deep_layer = Some((*block, deep_depth.max(*depth))),
+            None => deep_layer = Some((*block, *depth))
+        }
+    }
+    deep_layer
+}
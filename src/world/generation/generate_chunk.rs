@@ -1,123 +1,125 @@
-use crate::world::{generation::randomness::RandomnessFunctions, world::World};
 use noise::NoiseFn;
 use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::config::{CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition};
+use crate::world::generation::context::{GenerationBiome, GenerationContext};
+use crate::world::generation::randomness::RandomnessFunctions;
+use crate::world::generation::structure_placement::{place_structure, GeneratedChunk};
+use crate::world::generation::terrain::ColumnHeights;
+use crate::world::objects::chunk::{Chunk, AIR};
+
+// caves are carved where the 3d noise is above the threshold
+const CAVE_SCALE: f64 = 25.0;
+const CAVE_THRESHOLD: f64 = 0.5;
+// under the sea caves stay this far below the ground so the sea doesn't drain into them
+const SEA_FLOOR_CAVE_DEPTH: i64 = 4;
+
+pub fn generate_chunk(position: ChunkPosition, context: &GenerationContext) -> GeneratedChunk {
+    // structure blocks that belong to the chunks around this one go into its pending blocks
+    let mut generated = GeneratedChunk::new(Chunk::new());
+
+    // chunks above the terrain and the sea are air, only structures from below reach into them
+    let column = ColumnHeights::generate(position.0, position.2, &context.randomness_functions, &context.terrain);
+    if context.oceans.is_air_chunk(&column, position.1) {
+        return generated;
+    }
 
-use crate::config::CHUNK_LENGTH;
-
-pub fn generate_chunk(chunk_position_x: i64, chunk_position_y: i64, chunk_position_z: i64, world: World) -> ([u8; CHUNK_LENGTH], [u8; CHUNK_LENGTH]) {
-    let mut chunk: [u8; CHUNK_LENGTH] = [0; CHUNK_LENGTH];
-    let mut light: [u8; CHUNK_LENGTH] = [0; CHUNK_LENGTH];
-
-    let chunk_position_x = 32 * chunk_position_x;
-    let chunk_position_y = 32 * chunk_position_y;
-    let chunk_position_z = 32 * chunk_position_z;
-
-    /*for x in 0..32 {
-        let position_x = (x + chunk_position_x) as f32;
-        for z in 0..32 {
-            let position_z = (z + chunk_position_z) as f32;
-
-            let temperature = randomness_functions.noise.get([position_x as f64 / 2000.0, position_z as f64 / 2000.0]) as f32 * 50.0 + 30.0;
-            let moisture = randomness_functions.noise.get([position_x as f64 / 1000.0, position_z as f64 / 1000.0]) as f32 * 50.0 + 50.0;
-
-            let mut closest = (10000.0, &"".to_string());
-            let biomes = &world_data.biomes;
-
-            for biome_ in biomes.keys() {
-                let biome = &biomes[biome_];
-                let distance_from_params = ((biome.0 as f32 - temperature).powf(2.0) + (biome.1 as f32 - moisture).powf(2.0)).sqrt();
-                if distance_from_params < closest.0 {
-                    closest.0 = distance_from_params;
-                    closest.1 = biome_;
-                }
-            }
-
-            //let river_noise = randomness_functions.noise.get([position_x as f64 / 1000.0, position_z as f64 / 1000.0]);
-            //let river_noise_branching = randomness_functions.noise.get([position_x as f64 / 50.0, position_z as f64 / 50.0]);
-
-            for y in 0..32 {
-                let position_y = (y + chunk_position_y) as f32;
-
-                let block_layers = &world_data.biomes[closest.1].3;
-                let trees = &world_data.biomes[closest.1].5;
-
-                let terrain_max_height: f32 = ((
-                    (16.0 + randomness_functions.noise.get([position_x as f64 / 200.0, position_z as f64 / 200.0]) as f32 * 64.0) +
-                    (16.0 + randomness_functions.noise.get([position_x as f64 / 100.0, position_z as f64 / 100.0]) as f32 * 32.0) +
-                    (16.0 + randomness_functions.noise.get([position_x as f64 / 50.0, position_z as f64 / 50.0]) as f32 * 16.0) +
-                    (16.0 + randomness_functions.noise.get([position_x as f64 / 25.0, position_z as f64 / 25.0]) as f32 * 8.0) +
-                    (16.0 + randomness_functions.noise.get([position_x as f64 / 12.5, position_z as f64 / 12.5]) as f32 * 4.0)
-                ) / 5.0).floor();
-
-                let cave_noise = randomness_functions.noise.get([position_x as f64 / 25.0, position_y as f64 / 25.0, position_z as f64 / 25.0]);
+    // chunks that deep under the terrain are the deepest layer all the way
+    // through, they skip picking the biome and layer of every block
+    if let Some((block, _)) = context.deep_layer.filter(|(_, depth)| column.is_below_terrain(position.1, *depth)) {
+        generated.chunk.fill(block);
+        carve_caves(&mut generated.chunk, position, &column, context);
+        return generated;
+    }
 
-                if cave_noise < 0.7 {
-                    /*if position_y > terrain_max_height - (0.012 - river_noise.abs()) as f32 * 400.0 && river_noise.abs() < 0.012 {
-                        continue;
-                    } else if position_y > terrain_max_height - (0.006 - river_noise.abs() * river_noise_branching.abs()) as f32 * 600.0 && river_noise.abs() * river_noise_branching.abs() < 0.006 {
-                        continue;
-                    }*/
-                    let mut found_layer = false;
-                    for layer_ in 0..block_layers.len() {
-                        let layer = &block_layers[block_layers.len() - layer_ - 1];
-                        if found_layer { continue; }
-                        if position_y <= terrain_max_height - layer.1 as f32 {
-                            let mut block_index = 0;
-                            if layer.0.len() > 1 {
-                                block_index = rng.gen_range(0..layer.0.len());
-                            }
-                            chunk[(x * 32 * 32 + y * 32 + z) as usize] = world_data.block_index[&layer.0[block_index]] as i8;
-                            found_layer = true;
-                        }
-                    }
-                    if position_y == (terrain_max_height + 1.0).floor() && y > 0 && (
-                        chunk[(x * 32 * 32 + (y - 1) * 32 + z) as usize] == world_data.block_index["grass_1"] as i8 || 
-                        chunk[(x * 32 * 32 + (y - 1) * 32 + z) as usize] == world_data.block_index["grass_2"] as i8 || 
-                        chunk[(x * 32 * 32 + (y - 1) * 32 + z) as usize] == world_data.block_index["dirt"] as i8 || 
-                        chunk[(x * 32 * 32 + (y - 1) * 32 + z) as usize] == world_data.block_index["snow"] as i8 || 
-                        chunk[(x * 32 * 32 + (y - 1) * 32 + z) as usize] == world_data.block_index["sand_1"] as i8 || 
-                        chunk[(x * 32 * 32 + (y - 1) * 32 + z) as usize] == world_data.block_index["sand_2"] as i8
-                    ) {
-                        let tree_chosen = rng.gen_range(0..trees.len());
-                        let tree = &trees[tree_chosen];
-                        let folliage_number: f32 = rng.gen();
-                        if folliage_number < tree.1 {
-                            for block in &world_data.structures[&tree.0] {
-                                let block_position_x = block.position[0] as i64 + x;
-                                let block_position_y = block.position[1] as i64 + y;
-                                let block_position_z = block.position[2] as i64 + z;
-                                if block_position_x > 31 || block_position_x < 0 || block_position_y > 31 || block_position_y < 0 || block_position_z > 31 || block_position_z < 0 {
-                                    continue;
-                                }
-                                chunk[(block_position_x * 32 * 32 + block_position_y * 32 + block_position_z) as usize] = world_data.block_index[&block.block] as i8;
-                            }
-                        }
-                    }
-                }
+    // seeded from the chunk position, never from thread_rng, so regenerating
+    // a chunk always places the same blocks and trees
+    let mut rng = context.randomness_functions.chunk_rng(position.0, position.1, position.2);
+
+    let mut biomes = Vec::with_capacity(CHUNK_SIZE_X as usize * CHUNK_SIZE_Z as usize);
+    for x in 0..CHUNK_SIZE_X as usize {
+        for z in 0..CHUNK_SIZE_Z as usize {
+            let (world_x, _, world_z) = ChunkMap::world_position(position, (x, 0, z));
+            let biome = context.surface_biome(world_x, world_z);
+            fill_column(&mut generated.chunk, position, (x, z), &column, biome, context, &mut rng);
+            biomes.push(biome);
+        }
+    }
+    context.oceans.fill(&mut generated.chunk, position, &column);
+
+    // trees, folliage and buildings go last so they only grow into the air of the finished terrain
+    for x in 0..CHUNK_SIZE_X as usize {
+        for z in 0..CHUNK_SIZE_Z as usize {
+            let biome = biomes[x * CHUNK_SIZE_Z as usize + z];
+            for decorations in [&biome.trees, &biome.folliage, &biome.buildings] {
+                place_decoration(&mut generated, position, (x, z), &column, decorations, context, &mut rng);
             }
         }
     }
 
-    for x in 0..32 {
-        for z in 0..32 {
-            let mut light_level = 127;
-
-            for y in 0..32 {
-                let actual_y = 31 - y;
-
-                if chunk[(x * 32 * 32 + actual_y * 32 + z) as usize] > 0 {
-                    let is_transparent = &world_data.blocks[(chunk[(x * 32 * 32 + actual_y * 32 + z) as usize] - 1) as usize].5;
-                    if *is_transparent && light_level >= 127 / 12 {
-                        light_level -= 127 / 12;
-                    } else {
-                        //light_level = 0;
-                    }
+    generated
+}
+
+// the layers of the biome from the surface down, minus the caves
+fn fill_column(chunk: &mut Chunk, position: ChunkPosition, (x, z): (usize, usize), column: &ColumnHeights, biome: &GenerationBiome, context: &GenerationContext, rng: &mut ChaCha8Rng) {
+    let height = column.get(x, z);
+    for y in 0..CHUNK_SIZE_Y as usize {
+        let block_position = ChunkMap::world_position(position, (x, y, z));
+        let Some(layer) = biome.layer_at(height - block_position.1) else {
+            continue;
+        };
+        if is_cave(&context.randomness_functions, block_position, height, column.sea_level(x, z)) {
+            continue;
+        }
+        let block = if layer.len() > 1 { layer[rng.gen_range(0..layer.len())] } else { layer[0] };
+        chunk.set(x, y, z, block);
+    }
+}
+
+fn carve_caves(chunk: &mut Chunk, position: ChunkPosition, column: &ColumnHeights, context: &GenerationContext) {
+    for x in 0..CHUNK_SIZE_X as usize {
+        for z in 0..CHUNK_SIZE_Z as usize {
+            for y in 0..CHUNK_SIZE_Y as usize {
+                let block_position = ChunkMap::world_position(position, (x, y, z));
+                if is_cave(&context.randomness_functions, block_position, column.get(x, z), column.sea_level(x, z)) {
+                    chunk.set(x, y, z, AIR);
                 }
-
-                light[(x * 32 * 32 + actual_y * 32 + z) as usize] = light_level;
             }
         }
-    }*/
+    }
+}
 
-    return (chunk, light);
-}
\ No newline at end of file
+// whether a block below the surface is carved out
+pub fn is_cave(randomness_functions: &RandomnessFunctions, position: BlockPosition, height: i64, sea_level: i64) -> bool {
+    if height < sea_level && position.1 > height - SEA_FLOOR_CAVE_DEPTH {
+        return false;
+    }
+    let point = [position.0 as f64 / CAVE_SCALE, position.1 as f64 / CAVE_SCALE, position.2 as f64 / CAVE_SCALE];
+    randomness_functions.noise.get(point) > CAVE_THRESHOLD
+}
+
+// one structure of the list, like the biome's trees, may grow right above the
+// surface. it belongs to the chunk it starts in and reaches into the chunks around it
+fn place_decoration(generated: &mut GeneratedChunk, position: ChunkPosition, (x, z): (usize, usize), column: &ColumnHeights, decorations: &[(String, f32)], context: &GenerationContext, rng: &mut ChaCha8Rng) {
+    let height = column.get(x, z);
+    let (world_x, _, world_z) = ChunkMap::world_position(position, (x, 0, z));
+    let origin = (world_x, height + 1, world_z);
+    if decorations.is_empty() || ChunkMap::chunk_position(origin) != position {
+        return;
+    }
+    // nothing grows under water or over a cave
+    let sea_level = column.sea_level(x, z);
+    if height < sea_level || is_cave(&context.randomness_functions, (world_x, height, world_z), height, sea_level) {
+        return;
+    }
+
+    let (name, chance) = &decorations[rng.gen_range(0..decorations.len())];
+    if rng.gen::<f32>() >= *chance {
+        return;
+    }
+    if let Some(structure) = context.structures.get(name) {
+        place_structure(&mut generated.chunk, position, structure, origin, &mut generated.pending);
+    }
+}
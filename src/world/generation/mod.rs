@@ -1,2 +1,7 @@
-mod generate_chunk;
-pub mod randomness;
\ No newline at end of file
+pub mod generate_chunk;
+pub mod randomness;
+pub mod structure_placement;
+pub mod terrain;
+pub mod oceans;
+pub mod climate;
+pub mod context;
@@ -0,0 +1,45 @@
+use crate::config::{CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+use crate::world::blocks::BlockRegistry;
+use crate::world::chunk_map::{ChunkMap, ChunkPosition};
+use crate::world::generation::terrain::ColumnHeights;
+use crate::world::objects::chunk::{Block, Chunk};
+
+// what oceans and lakes are filled with, how high they go is the sea level
+// of the biomes in the column heights. the water is still, it only starts
+// flowing once something next to it changes
+#[derive(Clone, Copy, Debug)]
+pub struct Oceans {
+    // none when no water block is loaded
+    pub water: Option<Block>
+}
+impl Oceans {
+    pub fn new(blocks: &BlockRegistry) -> Self {
+        Self {
+            water: blocks.get_id("water").map(Block::from_id)
+        }
+    }
+
+    // whether the chunk at this height is above the terrain and the sea, so it is only air
+    pub fn is_air_chunk(&self, column: &ColumnHeights, chunk_y: i64) -> bool {
+        column.is_above_terrain(chunk_y) && (self.water.is_none() || chunk_y * CHUNK_SIZE_Y as i64 > column.max_sea_level())
+    }
+
+    // fills the air between the terrain and the sea level with water
+    pub fn fill(&self, chunk: &mut Chunk, chunk_position: ChunkPosition, column: &ColumnHeights) {
+        let Some(water) = self.water else {
+            return;
+        };
+        for x in 0..CHUNK_SIZE_X as usize {
+            for z in 0..CHUNK_SIZE_Z as usize {
+                let ground = column.get(x, z);
+                let sea_level = column.sea_level(x, z);
+                for y in 0..CHUNK_SIZE_Y as usize {
+                    let height = ChunkMap::world_position(chunk_position, (x, y, z)).1;
+                    if height > ground && height <= sea_level && chunk.get(x, y, z).is_air() {
+                        chunk.set(x, y, z, water);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,20 +1,73 @@
 use noise::Perlin;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 #[derive(Clone)]
 pub struct RandomnessFunctions {
-    //pub rng: ThreadRng,
+    pub seed: u64,
     pub noise: Perlin
 }
 impl RandomnessFunctions {
+    // a fresh world with a random seed
     pub fn new() -> Self {
         let mut rng = rand::thread_rng();
-        let seed: u32 = rng.gen_range(0..1000000);
+        Self::from_seed(rng.gen())
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
         println!("seed: {}", seed);
 
         RandomnessFunctions {
-            //rng: rand::thread_rng(),
-            noise: Perlin::new(seed)
+            seed,
+            noise: Perlin::new((seed ^ (seed >> 32)) as u32)
+        }
+    }
+
+    // seeds typed in by players, plain numbers are used as they are and
+    // anything else is hashed so "polydural" always gives the same world
+    pub fn from_string_seed(seed: &str) -> Self {
+        match seed.trim().parse::<u64>() {
+            Ok(seed) => Self::from_seed(seed),
+            Err(_) => Self::from_seed(Self::hash_string_seed(seed))
+        }
+    }
+
+    // fnv-1a, unlike the std hasher its output never changes between builds
+    pub fn hash_string_seed(seed: &str) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in seed.trim().bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash
+    }
+
+    // a hash of the seed and a position, the same inputs always give the same value
+    pub fn position_hash(&self, x: i64, y: i64, z: i64) -> u64 {
+        let mut hash = self.seed;
+        for value in [x, y, z] {
+            hash = mix(hash ^ mix(value as u64));
         }
+        hash
     }
-}
\ No newline at end of file
+
+    // every chunk gets its own rng that only depends on the seed and its
+    // position, so the terrain doesn't change with the order or thread that
+    // chunks happen to be generated on, chacha is used because the std rng
+    // may give other numbers on other platforms or versions of rand
+    pub fn chunk_rng(&self, chunk_x: i64, chunk_y: i64, chunk_z: i64) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(self.position_hash(chunk_x, chunk_y, chunk_z))
+    }
+}
+impl Default for RandomnessFunctions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// splitmix64 finalizer, spreads nearby inputs over the whole range
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
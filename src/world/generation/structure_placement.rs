@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition};
+use crate::world::objects::chunk::{Block, Chunk};
+use crate::world::structures::Structure;
+
+// a block and its position inside the chunk it is waiting for
+pub type PendingBlock = ((usize, usize, usize), Block);
+
+// what the generator hands back, blocks of structures that reach into other
+// chunks are in the pending blocks
+pub struct GeneratedChunk {
+    pub chunk: Chunk,
+    pub pending: PendingBlocks
+}
+impl GeneratedChunk {
+    pub fn new(chunk: Chunk) -> Self {
+        Self {
+            chunk,
+            pending: PendingBlocks::new()
+        }
+    }
+}
+
+// structure blocks waiting for their chunk to generate or load, by chunk
+#[derive(Clone, Default)]
+pub struct PendingBlocks {
+    blocks: HashMap<ChunkPosition, Vec<PendingBlock>>
+}
+impl PendingBlocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, position: BlockPosition, block: Block) {
+        self.blocks.entry(ChunkMap::chunk_position(position)).or_default()
+            .push((ChunkMap::local_position(position), block));
+    }
+
+    pub fn add_to_chunk(&mut self, chunk_position: ChunkPosition, blocks: Vec<PendingBlock>) {
+        self.blocks.entry(chunk_position).or_default().extend(blocks);
+    }
+
+    pub fn take(&mut self, chunk_position: ChunkPosition) -> Option<Vec<PendingBlock>> {
+        self.blocks.remove(&chunk_position)
+    }
+
+    pub fn contains(&self, chunk_position: ChunkPosition) -> bool {
+        self.blocks.contains_key(&chunk_position)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&ChunkPosition, &Vec<PendingBlock>)> {
+        self.blocks.iter()
+    }
+
+    pub fn into_chunks(self) -> impl Iterator<Item = (ChunkPosition, Vec<PendingBlock>)> {
+        self.blocks.into_iter()
+    }
+}
+
+// structures only grow into air, so it doesn't matter whether the terrain
+// or the structure got there first. returns whether anything changed
+pub fn apply_pending(chunk: &mut Chunk, blocks: &[PendingBlock]) -> bool {
+    let mut changed = false;
+    for ((x, y, z), block) in blocks {
+        if chunk.get(*x, *y, *z).is_air() {
+            chunk.set(*x, *y, *z, *block);
+            changed = true;
+        }
+    }
+    changed
+}
+
+// places a structure with its origin at a world position, the blocks that
+// land outside this chunk are kept in pending for the chunks they belong to
+pub fn place_structure(chunk: &mut Chunk, chunk_position: ChunkPosition, structure: &Structure, origin: BlockPosition, pending: &mut PendingBlocks) {
+    for (offset, block) in &structure.blocks {
+        if block.is_air() {
+            continue;
+        }
+        let position = (origin.0 + offset.0, origin.1 + offset.1, origin.2 + offset.2);
+        if ChunkMap::chunk_position(position) != chunk_position {
+            pending.add(position, *block);
+            continue;
+        }
+        let (x, y, z) = ChunkMap::local_position(position);
+        if chunk.get(x, y, z).is_air() {
+            chunk.set(x, y, z, *block);
+        }
+    }
+}
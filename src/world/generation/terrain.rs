@@ -0,0 +1,245 @@
+use noise::NoiseFn;
+use serde::Deserialize;
+
+use crate::config::{CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z, SEA_LEVEL};
+use crate::world::biomes::Biomes;
+use crate::world::chunk_map::ChunkPosition;
+use crate::world::generation::climate::{blend_weights, climate_at};
+use crate::world::generation::randomness::RandomnessFunctions;
+
+// how far above the sea the ground lies in worlds without biomes
+const DEFAULT_BASE_HEIGHT: f64 = 8.0;
+
+// how the octaves of a terrain profile are shaped
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseShape {
+    // rolling hills
+    #[default]
+    Normal,
+    // sharp crests where the noise crosses zero, for mountain ranges and dunes
+    Ridged,
+    // rounded bumps with creases between them
+    Billow
+}
+
+// the shape of a biome's terrain, "terrain" in its json. every octave is
+// lacunarity times as fine and persistence times as high as the one before,
+// the defaults are the hills every biome had before biomes had their own
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct TerrainProfile {
+    // how far above the sea the ground lies on average, the biome's height when left out
+    pub base_height: Option<f64>,
+    // how far the first octave rises and falls, in blocks
+    pub amplitude: f64,
+    // how many blocks wide the features of the first octave are
+    pub scale: f64,
+    pub octaves: u32,
+    pub lacunarity: f64,
+    pub persistence: f64,
+    pub shape: NoiseShape
+}
+impl TerrainProfile {
+    // the height at a column relative to the base height
+    pub fn sample(&self, x: i64, z: i64, randomness_functions: &RandomnessFunctions) -> f64 {
+        let mut frequency = 1.0 / self.scale;
+        let mut amplitude = self.amplitude;
+        let mut height = 0.0;
+        for _ in 0..self.octaves {
+            let noise = randomness_functions.noise.get([x as f64 * frequency, z as f64 * frequency]);
+            // both shapes are moved so they are about zero on average, like the plain noise
+            let value = match self.shape {
+                NoiseShape::Normal => noise,
+                NoiseShape::Ridged => (1.0 - noise.abs()).powi(2) * 2.0 - 1.0,
+                NoiseShape::Billow => noise.abs() * 2.0 - 0.5
+            };
+            height += value * amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+        height
+    }
+
+    // what serde can't check, the errors name the field like the biome's own
+    pub fn validate(&self) -> Result<(), String> {
+        if self.scale <= 0.0 {
+            return Err(format!("terrain.scale: {} is not above 0", self.scale));
+        }
+        if !(1..=16).contains(&self.octaves) {
+            return Err(format!("terrain.octaves: {} is not between 1 and 16", self.octaves));
+        }
+        if self.lacunarity <= 0.0 {
+            return Err(format!("terrain.lacunarity: {} is not above 0", self.lacunarity));
+        }
+        if self.persistence < 0.0 {
+            return Err(format!("terrain.persistence: {} is below 0", self.persistence));
+        }
+        Ok(())
+    }
+}
+impl Default for TerrainProfile {
+    fn default() -> Self {
+        Self {
+            base_height: None,
+            amplitude: 12.8,
+            scale: 200.0,
+            octaves: 5,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            shape: NoiseShape::Normal
+        }
+    }
+}
+
+// what the heightfield needs to know about a biome
+#[derive(Clone, Debug)]
+pub struct BiomeTerrain {
+    pub temperature: f32,
+    pub moisture: f32,
+    // how far above the sea the ground lies on average
+    pub base_height: f64,
+    // the biome's own sea level, or the one of the world when it leaves it at -1
+    pub sea_level: i64,
+    pub profile: TerrainProfile
+}
+impl BiomeTerrain {
+    pub fn height(&self, x: i64, z: i64, randomness_functions: &RandomnessFunctions) -> f64 {
+        self.sea_level as f64 + self.base_height + self.profile.sample(x, z, randomness_functions)
+    }
+}
+
+// the biomes as far as the shape of the terrain goes, unlike the generation
+// context it doesn't need any blocks so the world keeps one as well
+#[derive(Clone, Default)]
+pub struct Terrain {
+    biomes: Vec<BiomeTerrain>,
+    climates: Vec<(f32, f32)>
+}
+impl Terrain {
+    pub fn new(biomes: &Biomes) -> Self {
+        let biomes: Vec<BiomeTerrain> = biomes.iter()
+            .map(|biome| BiomeTerrain {
+                temperature: biome.temperature,
+                moisture: biome.moisture,
+                base_height: biome.terrain.base_height.unwrap_or(biome.height as f64),
+                sea_level: biome.get_sea_level().unwrap_or(SEA_LEVEL),
+                profile: biome.terrain.clone()
+            })
+            .collect();
+        let climates = biomes.iter().map(|biome| (biome.temperature, biome.moisture)).collect();
+
+        Self {
+            biomes,
+            climates
+        }
+    }
+
+    // the biomes a column mixes, by the index they were loaded in. empty without biomes
+    pub fn blend(&self, x: i64, z: i64, randomness_functions: &RandomnessFunctions) -> Vec<(usize, f32)> {
+        blend_weights(climate_at(x, z, randomness_functions), &self.climates)
+    }
+
+    // every biome around a column shapes the ground by its own profile, and
+    // the heights are mixed by how much each biome counts there
+    pub fn height(&self, x: i64, z: i64, randomness_functions: &RandomnessFunctions) -> f64 {
+        self.height_and_sea_level(x, z, randomness_functions).0
+    }
+
+    // the sea levels of the biomes are mixed like their heights, so the
+    // water steps down a block at a time between biomes with different ones
+    pub fn height_and_sea_level(&self, x: i64, z: i64, randomness_functions: &RandomnessFunctions) -> (f64, i64) {
+        if self.biomes.is_empty() {
+            return (SEA_LEVEL as f64 + DEFAULT_BASE_HEIGHT + TerrainProfile::default().sample(x, z, randomness_functions), SEA_LEVEL);
+        }
+        let mut height = 0.0;
+        let mut sea_level = 0.0;
+        for (index, weight) in self.blend(x, z, randomness_functions) {
+            let biome = &self.biomes[index];
+            height += biome.height(x, z, randomness_functions) * weight as f64;
+            sea_level += biome.sea_level as f64 * weight as f64;
+        }
+        (height, sea_level.round() as i64)
+    }
+}
+
+// the height of the terrain surface at a column, this is all the generator
+// knows about the shape of the world so anything that needs to know where the
+// ground is without generating it (like the streamer) asks this
+pub fn terrain_height(x: i64, z: i64, randomness_functions: &RandomnessFunctions, terrain: &Terrain) -> i64 {
+    terrain.height(x, z, randomness_functions).floor() as i64
+}
+
+// the terrain heights and sea levels of one column of chunks, vertical chunks
+// completely above the highest point are air and don't need to be generated at all
+#[derive(Clone)]
+pub struct ColumnHeights {
+    heights: Vec<i64>,
+    sea_levels: Vec<i64>,
+    min_height: i64,
+    max_height: i64,
+    max_sea_level: i64
+}
+impl ColumnHeights {
+    pub fn generate(chunk_x: i64, chunk_z: i64, randomness_functions: &RandomnessFunctions, terrain: &Terrain) -> Self {
+        let size_x = CHUNK_SIZE_X as i64;
+        let size_z = CHUNK_SIZE_Z as i64;
+        let mut heights = Vec::with_capacity((size_x * size_z) as usize);
+        let mut sea_levels = Vec::with_capacity((size_x * size_z) as usize);
+        for x in 0..size_x {
+            for z in 0..size_z {
+                let (height, sea_level) = terrain.height_and_sea_level(chunk_x * size_x + x, chunk_z * size_z + z, randomness_functions);
+                heights.push(height.floor() as i64);
+                sea_levels.push(sea_level);
+            }
+        }
+        let min_height = heights.iter().copied().min().unwrap_or(0);
+        let max_height = heights.iter().copied().max().unwrap_or(0);
+        let max_sea_level = sea_levels.iter().copied().max().unwrap_or(SEA_LEVEL);
+
+        Self {
+            heights,
+            sea_levels,
+            min_height,
+            max_height,
+            max_sea_level
+        }
+    }
+
+    // local x and z inside the chunk
+    pub fn get(&self, x: usize, z: usize) -> i64 {
+        self.heights[x * CHUNK_SIZE_Z as usize + z]
+    }
+
+    // the highest block the sea fills at local x and z
+    pub fn sea_level(&self, x: usize, z: usize) -> i64 {
+        self.sea_levels[x * CHUNK_SIZE_Z as usize + z]
+    }
+
+    pub fn max_sea_level(&self) -> i64 {
+        self.max_sea_level
+    }
+
+    pub fn min_height(&self) -> i64 {
+        self.min_height
+    }
+
+    pub fn max_height(&self) -> i64 {
+        self.max_height
+    }
+
+    // whether every block of the chunk at this height is above the terrain
+    pub fn is_above_terrain(&self, chunk_y: i64) -> bool {
+        chunk_y * CHUNK_SIZE_Y as i64 > self.max_height
+    }
+
+    // whether every block of the chunk at this height is at least depth blocks below the terrain
+    pub fn is_below_terrain(&self, chunk_y: i64, depth: i64) -> bool {
+        (chunk_y + 1) * CHUNK_SIZE_Y as i64 - 1 <= self.min_height - depth
+    }
+}
+
+// the column a chunk belongs to
+pub fn column_position(position: ChunkPosition) -> (i64, i64) {
+    (position.0, position.2)
+}
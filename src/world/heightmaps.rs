@@ -0,0 +1,117 @@
+use crate::config::{CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+use crate::world::blocks::BlockRegistry;
+use crate::world::objects::chunk::{Block, Chunk};
+
+// what counts as the top of a column depends on who is asking, trees and
+// spawning want the ground, light wants what it can't shine through and
+// rain wants what it lands on
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HeightmapKind {
+    Solid,
+    LightBlocking,
+    MotionBlocking
+}
+impl HeightmapKind {
+    pub const ALL: [HeightmapKind; 3] = [HeightmapKind::Solid, HeightmapKind::LightBlocking, HeightmapKind::MotionBlocking];
+
+    pub fn matches(&self, blocks: &BlockRegistry, block: Block) -> bool {
+        match self {
+            HeightmapKind::Solid => !block.is_air(),
+            HeightmapKind::LightBlocking => blocks.blocks_light(block),
+            HeightmapKind::MotionBlocking => blocks.collides(block)
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            HeightmapKind::Solid => 0,
+            HeightmapKind::LightBlocking => 1,
+            HeightmapKind::MotionBlocking => 2
+        }
+    }
+}
+
+const COLUMNS: usize = CHUNK_SIZE_X as usize * CHUNK_SIZE_Z as usize;
+
+// the highest block of every kind in each x z of one chunk. heights are
+// stored as local y + 1 so a column without such a block is 0, the world
+// combines the chunks of a column to find the top of it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Heightmaps {
+    heights: Vec<u8>
+}
+impl Heightmaps {
+    pub fn compute(chunk: &Chunk, blocks: &BlockRegistry) -> Self {
+        let mut heightmaps = Self {
+            heights: vec![0; COLUMNS * HeightmapKind::ALL.len()]
+        };
+        if chunk.is_empty() {
+            return heightmaps;
+        }
+        for x in 0..CHUNK_SIZE_X as usize {
+            for z in 0..CHUNK_SIZE_Z as usize {
+                for kind in HeightmapKind::ALL {
+                    let height = Self::scan(chunk, blocks, kind, x, CHUNK_SIZE_Y as usize, z);
+                    heightmaps.heights[Self::index(kind, x, z)] = height;
+                }
+            }
+        }
+        heightmaps
+    }
+
+    // the local y of the highest block of the kind, none when the column has none
+    pub fn get(&self, kind: HeightmapKind, x: usize, z: usize) -> Option<usize> {
+        match self.heights[Self::index(kind, x, z)] {
+            0 => None,
+            height => Some(height as usize - 1)
+        }
+    }
+
+    // whether any column has a block of the kind
+    pub fn any(&self, kind: HeightmapKind) -> bool {
+        let start = kind.index() * COLUMNS;
+        self.heights[start..start + COLUMNS].iter().any(|height| *height > 0)
+    }
+
+    // call after the block at the local position changed, only the column
+    // below it is scanned and only when its highest block went away
+    pub fn update(&mut self, chunk: &Chunk, blocks: &BlockRegistry, x: usize, y: usize, z: usize) {
+        let block = chunk.get(x, y, z);
+        for kind in HeightmapKind::ALL {
+            let index = Self::index(kind, x, z);
+            let height = self.heights[index] as usize;
+            if kind.matches(blocks, block) {
+                if y + 1 > height {
+                    self.heights[index] = (y + 1) as u8;
+                }
+            } else if y + 1 == height {
+                self.heights[index] = Self::scan(chunk, blocks, kind, x, y, z);
+            }
+        }
+    }
+
+    pub fn to_bytes(&self) -> &[u8] {
+        &self.heights
+    }
+
+    // none when the bytes aren't heightmaps of a chunk
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != COLUMNS * HeightmapKind::ALL.len() || bytes.iter().any(|height| *height > CHUNK_SIZE_Y) {
+            return None;
+        }
+        Some(Self {
+            heights: bytes.to_vec()
+        })
+    }
+
+    // the height of the highest block of the kind below the local y
+    fn scan(chunk: &Chunk, blocks: &BlockRegistry, kind: HeightmapKind, x: usize, below: usize, z: usize) -> u8 {
+        (0..below).rev()
+            .find(|y| kind.matches(blocks, chunk.get(x, *y, z)))
+            .map_or(0, |y| (y + 1) as u8)
+    }
+
+    fn index(kind: HeightmapKind, x: usize, z: usize) -> usize {
+        kind.index() * COLUMNS + x * CHUNK_SIZE_Z as usize + z
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::config::{CHUNK_LENGTH, CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+use crate::world::blocks::BlockRegistry;
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition, NEIGHBOUR_OFFSETS};
+use crate::world::objects::chunk::{Block, Chunk};
+
+pub const MAX_LIGHT: u8 = 15;
+
+const DOWN: (i64, i64, i64) = (0, -1, 0);
+
+// sky light comes from above and goes straight down without getting weaker,
+// block light comes from emissive blocks and spreads as red, green and blue
+// separately, so where two colours meet every channel keeps the brighter one.
+// all of them lose one level every block they spread sideways
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum LightChannel {
+    Sky,
+    Red,
+    Green,
+    Blue
+}
+impl LightChannel {
+    pub const ALL: [LightChannel; 4] = [LightChannel::Sky, LightChannel::Red, LightChannel::Green, LightChannel::Blue];
+    pub const BLOCK: [LightChannel; 3] = [LightChannel::Red, LightChannel::Green, LightChannel::Blue];
+}
+
+// one level per voxel, chunks that are lit the same everywhere (like the
+// sky or solid rock) don't keep an array at all
+#[derive(Clone, Debug)]
+enum LightArray {
+    Uniform(u8),
+    Values(Vec<u8>)
+}
+impl LightArray {
+    fn get(&self, index: usize) -> u8 {
+        match self {
+            LightArray::Uniform(level) => *level,
+            LightArray::Values(levels) => levels[index]
+        }
+    }
+
+    fn set(&mut self, index: usize, level: u8) {
+        match self {
+            LightArray::Uniform(uniform) if *uniform == level => {}
+            LightArray::Uniform(uniform) => {
+                let mut levels = vec![*uniform; CHUNK_LENGTH];
+                levels[index] = level;
+                *self = LightArray::Values(levels);
+            }
+            LightArray::Values(levels) => levels[index] = level
+        }
+    }
+}
+
+// the light of every voxel of one chunk. it isn't saved, chunks are lit
+// again when they load
+#[derive(Clone, Debug)]
+pub struct ChunkLight {
+    // in the order of LightChannel::ALL
+    channels: [LightArray; 4]
+}
+impl ChunkLight {
+    pub fn new() -> Self {
+        Self {
+            channels: [LightArray::Uniform(0), LightArray::Uniform(0), LightArray::Uniform(0), LightArray::Uniform(0)]
+        }
+    }
+
+    pub fn get(&self, channel: LightChannel, x: usize, y: usize, z: usize) -> u8 {
+        self.channels[channel as usize].get(Chunk::index(x, y, z))
+    }
+
+    pub fn set(&mut self, channel: LightChannel, x: usize, y: usize, z: usize, level: u8) {
+        self.channels[channel as usize].set(Chunk::index(x, y, z), level);
+    }
+}
+impl Default for ChunkLight {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// lights a chunk that was just added to the map and spreads light between it
+// and its loaded neighbours. open_sky tells for a local x z whether the sky
+// reaches the top of the chunk, it is only asked when the chunk above isn't loaded
+pub fn light_chunk(chunks: &mut ChunkMap, blocks: &BlockRegistry, position: ChunkPosition, open_sky: impl Fn(usize, usize) -> bool) {
+    let Some(chunk) = chunks.get_chunk(position) else {
+        return;
+    };
+    let size_x = CHUNK_SIZE_X as usize;
+    let size_y = CHUNK_SIZE_Y as usize;
+    let size_z = CHUNK_SIZE_Z as usize;
+    let mut engine = LightEngine::new(blocks);
+
+    // the sky comes down from the chunk above when it is loaded
+    let above = (position.0, position.1 + 1, position.2);
+    let mut open_columns = vec![false; size_x * size_z];
+    for x in 0..size_x {
+        for z in 0..size_z {
+            open_columns[x * size_z + z] = match chunks.get_chunk_light(above) {
+                Some(light) => light.get(LightChannel::Sky, x, 0, z) == MAX_LIGHT,
+                None => open_sky(x, z)
+            };
+        }
+    }
+
+    let mut light = ChunkLight::new();
+    if chunk.is_empty() && open_columns.iter().all(|open| *open) {
+        light.channels[LightChannel::Sky as usize] = LightArray::Uniform(MAX_LIGHT);
+    } else {
+        // how far down the sky reaches straight into every column
+        let mut floors = vec![size_y; size_x * size_z];
+        for x in 0..size_x {
+            for z in 0..size_z {
+                if !open_columns[x * size_z + z] {
+                    continue;
+                }
+                let mut floor = size_y;
+                while floor > 0 && !blocks.blocks_light(chunk.get(x, floor - 1, z)) {
+                    floor -= 1;
+                    light.set(LightChannel::Sky, x, floor, z, MAX_LIGHT);
+                }
+                floors[x * size_z + z] = floor;
+            }
+        }
+        // light only spreads sideways into columns the sky reaches less far down
+        for x in 0..size_x {
+            for z in 0..size_z {
+                let neighbours = [(x.wrapping_sub(1), z), (x + 1, z), (x, z.wrapping_sub(1)), (x, z + 1)];
+                let deepest = neighbours.iter()
+                    .filter(|(x, z)| *x < size_x && *z < size_z)
+                    .map(|(x, z)| floors[x * size_z + z])
+                    .max()
+                    .unwrap_or(0);
+                for y in floors[x * size_z + z]..size_y.min(deepest) {
+                    engine.spread(LightChannel::Sky, ChunkMap::world_position(position, (x, y, z)));
+                }
+            }
+        }
+    }
+    for (local_position, block) in chunk.iter_solid() {
+        let emission = blocks.emission(block);
+        for channel in LightChannel::BLOCK {
+            let level = emission[channel as usize - 1];
+            if level > 0 {
+                light.set(channel, local_position.0, local_position.1, local_position.2, level);
+                engine.spread(channel, ChunkMap::world_position(position, local_position));
+            }
+        }
+    }
+    chunks.set_chunk_light(position, light);
+
+    // light flows both ways over every border with a loaded chunk
+    for offset in NEIGHBOUR_OFFSETS {
+        let neighbour = (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2);
+        if chunks.get_chunk_light(neighbour).is_none() {
+            continue;
+        }
+        for (inside, outside) in border_positions(position, offset) {
+            for channel in LightChannel::ALL {
+                engine.spread(channel, inside);
+                engine.spread(channel, outside);
+            }
+        }
+    }
+
+    // the chunk may cover sky the chunk below thought it could see
+    let below = (position.0, position.1 - 1, position.2);
+    if let (Some(below_light), Some(light)) = (chunks.get_chunk_light(below), chunks.get_chunk_light(position)) {
+        for x in 0..size_x {
+            for z in 0..size_z {
+                if below_light.get(LightChannel::Sky, x, size_y - 1, z) == MAX_LIGHT && light.get(LightChannel::Sky, x, 0, z) != MAX_LIGHT {
+                    engine.remove(LightChannel::Sky, ChunkMap::world_position(below, (x, size_y - 1, z)), MAX_LIGHT);
+                }
+            }
+        }
+    }
+
+    engine.run(chunks);
+}
+
+// brings the light around a block up to date after the block changed
+pub fn update_light(chunks: &mut ChunkMap, blocks: &BlockRegistry, position: BlockPosition) {
+    let Some(block) = chunks.get_block(position) else {
+        return;
+    };
+    let mut engine = LightEngine::new(blocks);
+    for channel in LightChannel::ALL {
+        let Some(old_level) = chunks.get_light(position, channel) else {
+            continue;
+        };
+        engine.remove(channel, position, old_level);
+        let emission = emission_level(blocks, block, channel);
+        if emission > 0 {
+            engine.emit(position, channel, emission);
+        }
+        // whatever is around may shine into the space the block left
+        for offset in NEIGHBOUR_OFFSETS {
+            engine.spread(channel, (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2));
+        }
+    }
+    engine.run(chunks);
+}
+
+// a breadth first flood fill per channel. removals run first, they darken
+// everything that got its light from a removed source and hand the brighter
+// edges they run into to the spreading queue, which fills the dark back in
+struct LightEngine<'a> {
+    blocks: &'a BlockRegistry,
+    removals: [VecDeque<(BlockPosition, u8)>; 4],
+    spreads: [VecDeque<BlockPosition>; 4],
+    // new emitters, set before anything is spread
+    emitters: Vec<(BlockPosition, LightChannel, u8)>,
+    changed: HashSet<ChunkPosition>
+}
+impl<'a> LightEngine<'a> {
+    fn new(blocks: &'a BlockRegistry) -> Self {
+        Self {
+            blocks,
+            removals: Default::default(),
+            spreads: Default::default(),
+            emitters: Vec::new(),
+            changed: HashSet::new()
+        }
+    }
+
+    fn spread(&mut self, channel: LightChannel, position: BlockPosition) {
+        self.spreads[channel as usize].push_back(position);
+    }
+
+    // the level is what the position had before it went dark
+    fn remove(&mut self, channel: LightChannel, position: BlockPosition, level: u8) {
+        self.removals[channel as usize].push_back((position, level));
+    }
+
+    fn emit(&mut self, position: BlockPosition, channel: LightChannel, level: u8) {
+        self.emitters.push((position, channel, level));
+    }
+
+    fn run(mut self, chunks: &mut ChunkMap) {
+        for channel in LightChannel::ALL {
+            let starts: Vec<BlockPosition> = self.removals[channel as usize].iter().map(|(position, _)| *position).collect();
+            for position in starts {
+                self.set(chunks, channel, position, 0);
+            }
+            self.run_removals(chunks, channel);
+        }
+        for (position, channel, level) in std::mem::take(&mut self.emitters) {
+            self.set(chunks, channel, position, level);
+            self.spread(channel, position);
+        }
+        for channel in LightChannel::ALL {
+            self.run_spreads(chunks, channel);
+        }
+
+        // faces take their light from the block in front, which may be in the next chunk
+        for position in self.changed {
+            chunks.mark_changed(position);
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour = (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2);
+                if chunks.contains_chunk(neighbour) {
+                    chunks.mark_changed(neighbour);
+                }
+            }
+        }
+    }
+
+    fn run_removals(&mut self, chunks: &mut ChunkMap, channel: LightChannel) {
+        while let Some((position, level)) = self.removals[channel as usize].pop_front() {
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour = (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2);
+                let Some(neighbour_level) = chunks.get_light(neighbour, channel) else {
+                    continue;
+                };
+                if neighbour_level == 0 {
+                    continue;
+                }
+                // full sky light below full sky light came from it
+                let fed_from_above = channel == LightChannel::Sky && offset == DOWN && level == MAX_LIGHT && neighbour_level == MAX_LIGHT;
+                if neighbour_level < level || fed_from_above {
+                    self.set(chunks, channel, neighbour, 0);
+                    self.removals[channel as usize].push_back((neighbour, neighbour_level));
+                    // emitters keep shining no matter what went dark around them
+                    let emission = chunks.get_block(neighbour).map_or(0, |block| emission_level(self.blocks, block, channel));
+                    if emission > 0 {
+                        self.emit(neighbour, channel, emission);
+                    }
+                } else {
+                    self.spread(channel, neighbour);
+                }
+            }
+        }
+    }
+
+    fn run_spreads(&mut self, chunks: &mut ChunkMap, channel: LightChannel) {
+        while let Some(position) = self.spreads[channel as usize].pop_front() {
+            let Some(level) = chunks.get_light(position, channel) else {
+                continue;
+            };
+            if level <= 1 {
+                continue;
+            }
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour = (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2);
+                let Some(block) = chunks.get_block(neighbour) else {
+                    continue;
+                };
+                if self.blocks.blocks_light(block) {
+                    continue;
+                }
+                let new_level = if channel == LightChannel::Sky && offset == DOWN && level == MAX_LIGHT {
+                    MAX_LIGHT
+                } else {
+                    level - 1
+                };
+                if chunks.get_light(neighbour, channel).is_some_and(|current| current < new_level) {
+                    self.set(chunks, channel, neighbour, new_level);
+                    self.spread(channel, neighbour);
+                }
+            }
+        }
+    }
+
+    fn set(&mut self, chunks: &mut ChunkMap, channel: LightChannel, position: BlockPosition, level: u8) {
+        if chunks.set_light(position, channel, level) {
+            self.changed.insert(ChunkMap::chunk_position(position));
+        }
+    }
+}
+
+fn emission_level(blocks: &BlockRegistry, block: Block, channel: LightChannel) -> u8 {
+    match channel {
+        LightChannel::Sky => 0,
+        channel => blocks.emission(block)[channel as usize - 1]
+    }
+}
+
+// pairs of a block on the border of the chunk and the block next to it in the neighbour
+fn border_positions(position: ChunkPosition, offset: (i64, i64, i64)) -> Vec<(BlockPosition, BlockPosition)> {
+    let size = [CHUNK_SIZE_X as usize, CHUNK_SIZE_Y as usize, CHUNK_SIZE_Z as usize];
+    let axis = if offset.0 != 0 { 0 } else if offset.1 != 0 { 1 } else { 2 };
+    let step = offset.0 + offset.1 + offset.2;
+    let edge = if step > 0 { size[axis] - 1 } else { 0 };
+    let tangents: Vec<usize> = (0..3).filter(|other| *other != axis).collect();
+
+    let mut positions = Vec::with_capacity(size[tangents[0]] * size[tangents[1]]);
+    for first in 0..size[tangents[0]] {
+        for second in 0..size[tangents[1]] {
+            let mut local = [0; 3];
+            local[axis] = edge;
+            local[tangents[0]] = first;
+            local[tangents[1]] = second;
+            let inside = ChunkMap::world_position(position, (local[0], local[1], local[2]));
+            positions.push((inside, (inside.0 + offset.0, inside.1 + offset.1, inside.2 + offset.2)));
+        }
+    }
+    positions
+}
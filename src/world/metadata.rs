@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::world::objects::chunk::BlockId;
+use crate::world::time::WorldTime;
+use crate::world::weather::WeatherSettings;
+
+pub const METADATA_VERSION: u32 = 1;
+
+// everything about a world that isn't stored in its chunks, saved as
+// world.json in the save directory
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorldMetadata {
+    pub version: u32,
+    pub name: String,
+    pub seed: u64,
+    // creator:name to the id the block has in this world's chunks
+    #[serde(default)]
+    pub block_ids: HashMap<String, BlockId>,
+    // worlds saved before the clock existed start in the morning
+    #[serde(default)]
+    pub time: WorldTime,
+    #[serde(default)]
+    pub weather: WeatherSettings
+}
+impl WorldMetadata {
+    pub fn new(name: &str, seed: u64) -> Self {
+        Self {
+            version: METADATA_VERSION,
+            name: name.to_string(),
+            seed,
+            block_ids: HashMap::new(),
+            time: WorldTime::new(),
+            weather: WeatherSettings::default()
+        }
+    }
+
+    // none when the world has never been saved
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        let metadata: Self = serde_json::from_str(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), err)))?;
+        if metadata.version > METADATA_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: unsupported world version {}", path.display(), metadata.version)));
+        }
+        Ok(Some(metadata))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, content)
+    }
+}
@@ -1,6 +1,25 @@
 pub mod world;
 pub mod object;
 pub mod objects;
+pub mod chunk_map;
+pub mod streaming;
+pub mod storage;
+pub mod metadata;
 pub mod generation;
+pub mod blocks;
+pub mod heightmaps;
+pub mod light;
+pub mod fluids;
+pub mod random_ticks;
+pub mod block_updates;
+pub mod falling_blocks;
+pub mod time;
+pub mod weather;
+pub mod block_states;
+pub mod block_entities;
+pub mod edit_journal;
+pub mod world_edit;
+pub mod commands;
+pub mod structures;
 pub mod biomes;
 pub mod assets;
\ No newline at end of file
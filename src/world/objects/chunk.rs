@@ -1,721 +1,354 @@
-pub fn render_chunk(chunk: &Vec<i8>, light_data: &Vec<i8>, game_data: &common::GameData, chunks: &HashMap<(i64, i64, i64), Vec<i8>>, world_data: &world::WorldData, chunk_position_x: i64, chunk_position_y: i64, chunk_position_z: i64) -> (Vec<[f64; 3]>, Vec<[i8; 3]>, Vec<[f32; 3]>, Vec<[f32; 2]>, Vec<[f64; 3]>, Vec<[i8; 3]>, Vec<[f32; 3]>, Vec<[f32; 2]>) {
-    let mut vertices: Vec<[f64; 3]> = Vec::new();
-    let mut normals: Vec<[i8; 3]> = Vec::new();
-    let mut colors: Vec<[f32; 3]> = Vec::new();
-    let mut uvs: Vec<[f32; 2]> = Vec::new();
-
-    let mut vertices_transparent: Vec<[f64; 3]> = Vec::new();
-    let mut normals_transparent: Vec<[i8; 3]> = Vec::new();
-    let mut colors_transparent: Vec<[f32; 3]> = Vec::new();
-    let mut uvs_transparent: Vec<[f32; 2]> = Vec::new();
-
-    let atlas_width = 8.0;
-    let atlas_height = 8.0;
-
-    let world_data_clone = &world_data;
-
-    for x in 0..32 {
-        for z in 0..32 {
-            for y in 0..32 {
-                let block_id = get_block(&chunk, x, y, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z);
-                if block_id == 0 { continue; }
-
-                let light_level = light_data[(x * 32 * 32 + y * 32 + z) as usize] as f32 / 127.0;
-
-                let shape_index = world_data.shape_index[&world_data.blocks[(block_id - 1) as usize].3];
-                let elements = &world_data.shapes[shape_index - 1].1;
-                let is_transparent = &world_data.blocks[(block_id - 1) as usize].5;
-
-                if !is_transparent {
-                    for element in elements {
-                        let vertices_from = element.from;
-                        let vertices_to = element.to;
-
-                        let mut directions = vec![
-                            false, false, false, false, false, false,
-                            false, false, false, false, false, false,
-                            false, false, false, false, false, false
-                        ];
-
-                        if get_block_transparent(&chunk, x + 1, y, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[0] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x - 1, y, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[1] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x, y + 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[2] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x, y - 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[3] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x, y, z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[4] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x, y, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[5] = true;
-                        }
-
-                        if get_block(&chunk, x + 1, y + 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // right top (6)
-                            directions[6] = true;
-                        }
-                        if get_block(&chunk, x + 1, y - 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // right bottom (7)
-                            directions[7] = true;
-                        }
-                        if get_block(&chunk, x - 1, y + 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // left top (8)
-                            directions[8] = true;
-                        }
-                        if get_block(&chunk, x - 1, y - 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // left bottom (9)
-                            directions[9] = true;
-                        }
-
-                        if get_block(&chunk, x, y + 1, z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // front top (10)
-                            directions[10] = true;
-                        }
-                        if get_block(&chunk, x, y - 1, z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // front bottom (11)
-                            directions[11] = true;
-                        }
-                        if get_block(&chunk, x, y + 1, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // back top (12)
-                            directions[12] = true;
-                        }
-                        if get_block(&chunk, x, y - 1, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // back bottom (13)
-                            directions[13] = true;
-                        }
-
-                        if get_block(&chunk, x + 1, y, z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // right front (14)
-                            directions[14] = true;
-                        }
-                        if get_block(&chunk, x + 1, y, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // right back (15)
-                            directions[15] = true;
-                        }
-                        if get_block(&chunk, x - 1, y , z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // left front (16)
-                            directions[16] = true;
-                        }
-                        if get_block(&chunk, x - 1, y, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // left back (17)
-                            directions[17] = true;
-                        }
-
-                        let block_position_x = x as f64;
-                        let block_position_y = y as f64;
-                        let block_position_z = z as f64;
-
-                        if !directions[0] {
-                            vertices.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[0] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[0] as f32 / atlas_height).floor();
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals.push([1, 0, 0]);
-                            normals.push([1, 0, 0]);
-                            normals.push([1, 0, 0]);
-                            normals.push([1, 0, 0]);
-                            normals.push([1, 0, 0]);
-                            normals.push([1, 0, 0]);
-
-                            if !directions[7] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            if !directions[7] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                        }
-                        if !directions[1] {
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[1] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[1] as f32 / atlas_height).floor();
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals.push([-1, 0, 0]);
-                            normals.push([-1, 0, 0]);
-                            normals.push([-1, 0, 0]);
-                            normals.push([-1, 0, 0]);
-                            normals.push([-1, 0, 0]);
-                            normals.push([-1, 0, 0]);
-
-                            if !directions[9] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            if !directions[9] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                        }
-                        if !directions[2] {
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[2] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[2] as f32 / atlas_height).floor();
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals.push([0, 1, 0]);
-                            normals.push([0, 1, 0]);
-                            normals.push([0, 1, 0]);
-                            normals.push([0, 1, 0]);
-                            normals.push([0, 1, 0]);
-                            normals.push([0, 1, 0]);
-
-                            if !directions[8] && ! directions[10] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[6] && ! directions[10] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[8] && ! directions[12] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[6] && ! directions[10] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[6] && ! directions[12] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                        }
-                        if !directions[3] {
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[3] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[3] as f32 / atlas_height).floor();
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals.push([0, -1, 0]);
-                            normals.push([0, -1, 0]);
-                            normals.push([0, -1, 0]);
-                            normals.push([0, -1, 0]);
-                            normals.push([0, -1, 0]);
-                            normals.push([0, -1, 0]);
-
-                            if !directions[9] && !directions[13] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[7] && !directions[13] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[9] && !directions[11] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[7] && !directions[13] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[7] && !directions[11] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                        }
-                        if !directions[4] {
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[4] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[4] as f32 / atlas_height).floor();
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals.push([0, 0, 1]);
-                            normals.push([0, 0, 1]);
-                            normals.push([0, 0, 1]);
-                            normals.push([0, 0, 1]);
-                            normals.push([0, 0, 1]);
-                            normals.push([0, 0, 1]);
-
-                            if !directions[11] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            if !directions[11] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                        }
-                        if !directions[5] {
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[5] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[5] as f32 / atlas_height).floor();
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals.push([0, 0, -1]);
-                            normals.push([0, 0, -1]);
-                            normals.push([0, 0, -1]);
-                            normals.push([0, 0, -1]);
-                            normals.push([0, 0, -1]);
-                            normals.push([0, 0, -1]);
-
-                            if !directions[13] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            if !directions[13] {
-                                colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                        }
-                    }
-                } else {
-                    for element in elements {
-                        let vertices_from = element.from;
-                        let vertices_to = element.to;
-
-                        let mut directions = vec![
-                            false, false, false, false, false, false,
-                            false, false, false, false, false, false,
-                            false, false, false, false, false, false
-                        ];
-
-                        if get_block_transparent(&chunk, x + 1, y, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[0] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x - 1, y, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[1] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x, y + 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[2] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x, y - 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[3] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x, y, z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[4] = true;
-                        }
-
-                        if get_block_transparent(&chunk, x, y, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) == false {
-                            directions[5] = true;
-                        }
-
-                        if get_block(&chunk, x + 1, y + 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // right top (6)
-                            directions[6] = true;
-                        }
-                        if get_block(&chunk, x + 1, y - 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // right bottom (7)
-                            directions[7] = true;
-                        }
-                        if get_block(&chunk, x - 1, y + 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // left top (8)
-                            directions[8] = true;
-                        }
-                        if get_block(&chunk, x - 1, y - 1, z, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // left bottom (9)
-                            directions[9] = true;
-                        }
-
-                        if get_block(&chunk, x, y + 1, z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // front top (10)
-                            directions[10] = true;
-                        }
-                        if get_block(&chunk, x, y - 1, z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // front bottom (11)
-                            directions[11] = true;
-                        }
-                        if get_block(&chunk, x, y + 1, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // back top (12)
-                            directions[12] = true;
-                        }
-                        if get_block(&chunk, x, y - 1, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // back bottom (13)
-                            directions[13] = true;
-                        }
-
-                        if get_block(&chunk, x + 1, y, z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // right front (14)
-                            directions[14] = true;
-                        }
-                        if get_block(&chunk, x + 1, y, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // right back (15)
-                            directions[15] = true;
-                        }
-                        if get_block(&chunk, x - 1, y , z + 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // left front (16)
-                            directions[16] = true;
-                        }
-                        if get_block(&chunk, x - 1, y, z - 1, &game_data, chunks, &world_data_clone, chunk_position_x, chunk_position_y, chunk_position_z) > 0 { // left back (17)
-                            directions[17] = true;
-                        }
-
-                        let block_position_x = x as f64;
-                        let block_position_y = y as f64;
-                        let block_position_z = z as f64;
-
-                        if !directions[0] && vertices_from[1] != vertices_to[1] && vertices_from[2] != vertices_to[2] {
-                            vertices_transparent.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([ ( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[0] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[0] as f32 / atlas_height).floor();
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals_transparent.push([1, 0, 0]);
-                            normals_transparent.push([1, 0, 0]);
-                            normals_transparent.push([1, 0, 0]);
-                            normals_transparent.push([1, 0, 0]);
-                            normals_transparent.push([1, 0, 0]);
-                            normals_transparent.push([1, 0, 0]);
-
-                            if !directions[7] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            if !directions[7] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                        }
-                        if !directions[1] && vertices_from[1] != vertices_to[1] && vertices_from[2] != vertices_to[2] {
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[1] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[1] as f32 / atlas_height).floor();
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals_transparent.push([-1, 0, 0]);
-                            normals_transparent.push([-1, 0, 0]);
-                            normals_transparent.push([-1, 0, 0]);
-                            normals_transparent.push([-1, 0, 0]);
-                            normals_transparent.push([-1, 0, 0]);
-                            normals_transparent.push([-1, 0, 0]);
-
-                            if !directions[9] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            if !directions[9] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                        }
-                        if !directions[2] && vertices_from[0] != vertices_to[0] && vertices_from[2] != vertices_to[2] {
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[2] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[2] as f32 / atlas_height).floor();
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals_transparent.push([0, 1, 0]);
-                            normals_transparent.push([0, 1, 0]);
-                            normals_transparent.push([0, 1, 0]);
-                            normals_transparent.push([0, 1, 0]);
-                            normals_transparent.push([0, 1, 0]);
-                            normals_transparent.push([0, 1, 0]);
-
-                            if !directions[8] && ! directions[10] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[6] && ! directions[10] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[8] && ! directions[12] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[6] && ! directions[10] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[6] && ! directions[12] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                        }
-                        if !directions[3] && vertices_from[0] != vertices_to[0] && vertices_from[2] != vertices_to[2] {
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[3] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[3] as f32 / atlas_height).floor();
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals_transparent.push([0, -1, 0]);
-                            normals_transparent.push([0, -1, 0]);
-                            normals_transparent.push([0, -1, 0]);
-                            normals_transparent.push([0, -1, 0]);
-                            normals_transparent.push([0, -1, 0]);
-                            normals_transparent.push([0, -1, 0]);
-
-                            if !directions[9] && !directions[13] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[7] && !directions[13] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[9] && !directions[11] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[7] && !directions[13] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            if !directions[7] && !directions[11] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                        }
-                        if !directions[4] && vertices_from[0] != vertices_to[0] && vertices_from[1] != vertices_to[1] {
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0, (1.0 - (2.0 - vertices_to[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[4] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[4] as f32 / atlas_height).floor();
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals_transparent.push([0, 0, 1]);
-                            normals_transparent.push([0, 0, 1]);
-                            normals_transparent.push([0, 0, 1]);
-                            normals_transparent.push([0, 0, 1]);
-                            normals_transparent.push([0, 0, 1]);
-                            normals_transparent.push([0, 0, 1]);
-
-                            if !directions[11] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            if !directions[11] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                        }
-                        if !directions[5] && vertices_from[0] != vertices_to[0] && vertices_from[1] != vertices_to[1] {
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([( 1.0 - (2.0 - vertices_to[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0,(-1.0 + (vertices_from[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-                            vertices_transparent.push([(-1.0 + (vertices_from[0] / 8.0)) + block_position_x * 2.0 + chunk_position_x as f64 * 64.0, (1.0 - (2.0 - vertices_to[1] / 8.0)) + block_position_y * 2.0 + chunk_position_y as f64 * 64.0,(-1.0 + (vertices_from[2] / 8.0)) + block_position_z * 2.0 + chunk_position_z as f64 * 64.0]);
-
-                            let uv_x = (world_data.blocks[(block_id - 1) as usize].1[5] as f32 % atlas_width).floor();
-                            let uv_y = (world_data.blocks[(block_id - 1) as usize].1[5] as f32 / atlas_height).floor();
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([0.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 1.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-                            uvs_transparent.push([1.0 / atlas_width + 1.0 / atlas_width * (uv_x), 0.0 / atlas_height + 1.0 / atlas_height * (uv_y)]);
-
-                            normals_transparent.push([0, 0, -1]);
-                            normals_transparent.push([0, 0, -1]);
-                            normals_transparent.push([0, 0, -1]);
-                            normals_transparent.push([0, 0, -1]);
-                            normals_transparent.push([0, 0, -1]);
-                            normals_transparent.push([0, 0, -1]);
-
-                            if !directions[13] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            if !directions[13] {
-                                colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                            } else  {
-                                colors_transparent.push([0.5 * light_level, 0.5 * light_level, 0.5 * light_level]);
-                            }
-                            colors_transparent.push([1.0 * light_level, 1.0 * light_level, 1.0 * light_level]);
-                        }
-                    }
-                }
+use std::collections::HashMap;
+
+use crate::config::{CHUNK_LENGTH, CHUNK_SIZE_X, CHUNK_SIZE_Y, CHUNK_SIZE_Z};
+use crate::world::block_entities::BlockEntity;
+use crate::world::heightmaps::Heightmaps;
+
+pub type BlockId = u16;
+// the state properties of a block packed into bits, the registry knows the
+// layout for every block
+pub type BlockState = u16;
+
+// what a chunk stores for every voxel, the block and its state
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Block {
+    pub id: BlockId,
+    pub state: BlockState
+}
+impl Block {
+    pub const fn new(id: BlockId, state: BlockState) -> Self {
+        Self { id, state }
+    }
+
+    // a block in its default state
+    pub const fn from_id(id: BlockId) -> Self {
+        Self { id, state: 0 }
+    }
+
+    pub fn is_air(&self) -> bool {
+        self.id == AIR.id
+    }
+}
+
+pub const AIR: Block = Block::from_id(0);
+
+// a chunk keeps a small palette of the blocks (with their states) it contains and stores every
+// voxel as a bit packed index into that palette, so memory grows with the
+// amount of different blocks instead of the volume. a chunk that is only
+// one block (which is most of the sky) keeps no index data at all
+#[derive(Clone)]
+pub struct Chunk {
+    palette: Vec<Block>,
+    // how many voxels use each palette entry, unused entries get reused
+    counts: Vec<u32>,
+    // zero while the chunk is uniform
+    bits_per_block: u8,
+    data: Vec<u64>,
+    // extra data of single blocks, keyed by their local position
+    block_entities: HashMap<(usize, usize, usize), Box<dyn BlockEntity>>,
+    // the world keeps these current, none until it computed them
+    heightmaps: Option<Heightmaps>
+}
+impl Chunk {
+    pub fn new() -> Self {
+        Self::filled(AIR)
+    }
+
+    pub fn filled(block: Block) -> Self {
+        Self {
+            palette: vec![block],
+            counts: vec![CHUNK_LENGTH as u32],
+            bits_per_block: 0,
+            data: Vec::new(),
+            block_entities: HashMap::new(),
+            heightmaps: None
+        }
+    }
+
+    pub fn from_blocks(blocks: &[Block]) -> Self {
+        assert_eq!(blocks.len(), CHUNK_LENGTH, "a chunk needs exactly CHUNK_LENGTH blocks");
+
+        let mut chunk = Self::filled(blocks[0]);
+        for (index, block) in blocks.iter().enumerate() {
+            chunk.set_index(index, *block);
+        }
+        chunk
+    }
+
+    pub fn index(x: usize, y: usize, z: usize) -> usize {
+        x * CHUNK_SIZE_Y as usize * CHUNK_SIZE_Z as usize + y * CHUNK_SIZE_Z as usize + z
+    }
+
+    pub fn position(index: usize) -> (usize, usize, usize) {
+        let size_y = CHUNK_SIZE_Y as usize;
+        let size_z = CHUNK_SIZE_Z as usize;
+        (index / (size_y * size_z), (index / size_z) % size_y, index % size_z)
+    }
+
+    pub fn in_bounds(x: i64, y: i64, z: i64) -> bool {
+        x >= 0 && y >= 0 && z >= 0 &&
+        x < CHUNK_SIZE_X as i64 && y < CHUNK_SIZE_Y as i64 && z < CHUNK_SIZE_Z as i64
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Block {
+        self.get_index(Self::index(x, y, z))
+    }
+
+    pub fn get_index(&self, index: usize) -> Block {
+        self.palette[self.palette_index(index)]
+    }
+
+    // returns the block that was there before
+    pub fn set(&mut self, x: usize, y: usize, z: usize, block: Block) -> Block {
+        self.set_index(Self::index(x, y, z), block)
+    }
+
+    pub fn set_index(&mut self, index: usize, block: Block) -> Block {
+        let old_palette_index = self.palette_index(index);
+        let old_block = self.palette[old_palette_index];
+        if old_block == block {
+            return old_block;
+        }
+
+        let palette_index = self.palette_entry(block);
+        self.write_palette_index(index, palette_index);
+        self.counts[old_palette_index] -= 1;
+        self.counts[palette_index] += 1;
+
+        old_block
+    }
+
+    // replaces every block, block entities and heightmaps are dropped with them
+    pub fn fill(&mut self, block: Block) {
+        *self = Self::filled(block);
+    }
+
+    // the block the whole chunk consists of, if it is all the same
+    pub fn uniform_block(&self) -> Option<Block> {
+        let mut used = self.counts.iter().enumerate().filter(|(_, count)| **count > 0);
+        match (used.next(), used.next()) {
+            (Some((palette_index, _)), None) => Some(self.palette[palette_index]),
+            _ => None
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uniform_block() == Some(AIR)
+    }
+
+    pub fn count(&self, block: Block) -> u32 {
+        match self.palette.iter().position(|entry| *entry == block) {
+            Some(palette_index) => self.counts[palette_index],
+            None => 0
+        }
+    }
+
+    // every block that is used at least once in this chunk
+    pub fn palette(&self) -> impl Iterator<Item = Block> + '_ {
+        self.palette.iter().zip(self.counts.iter()).filter(|(_, count)| **count > 0).map(|(block, _)| *block)
+    }
+
+    pub fn bits_per_block(&self) -> u8 {
+        self.bits_per_block
+    }
+
+    // iterates over every voxel as ((x, y, z), block)
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize, usize), Block)> + '_ {
+        (0..CHUNK_LENGTH).map(move |index| (Self::position(index), self.get_index(index)))
+    }
+
+    // same as iter, but skips air so mostly empty chunks are cheap to walk
+    pub fn iter_solid(&self) -> impl Iterator<Item = ((usize, usize, usize), Block)> + '_ {
+        let length = if self.is_empty() { 0 } else { CHUNK_LENGTH };
+        (0..length)
+            .map(move |index| (Self::position(index), self.get_index(index)))
+            .filter(|(_, block)| !block.is_air())
+    }
+
+    pub fn to_blocks(&self) -> Vec<Block> {
+        (0..CHUNK_LENGTH).map(|index| self.get_index(index)).collect()
+    }
+
+    pub fn get_block_entity(&self, x: usize, y: usize, z: usize) -> Option<&dyn BlockEntity> {
+        self.block_entities.get(&(x, y, z)).map(|block_entity| block_entity.as_ref())
+    }
+
+    pub fn get_block_entity_mut(&mut self, x: usize, y: usize, z: usize) -> Option<&mut (dyn BlockEntity + 'static)> {
+        self.block_entities.get_mut(&(x, y, z)).map(|block_entity| block_entity.as_mut())
+    }
+
+    // returns the block entity that was there before
+    pub fn set_block_entity(&mut self, x: usize, y: usize, z: usize, block_entity: Box<dyn BlockEntity>) -> Option<Box<dyn BlockEntity>> {
+        self.block_entities.insert((x, y, z), block_entity)
+    }
+
+    pub fn remove_block_entity(&mut self, x: usize, y: usize, z: usize) -> Option<Box<dyn BlockEntity>> {
+        self.block_entities.remove(&(x, y, z))
+    }
+
+    // iterates over every block entity as ((x, y, z), block entity)
+    pub fn block_entities(&self) -> impl Iterator<Item = ((usize, usize, usize), &dyn BlockEntity)> {
+        self.block_entities.iter().map(|(position, block_entity)| (*position, block_entity.as_ref()))
+    }
+
+    pub fn block_entity_positions(&self) -> Vec<(usize, usize, usize)> {
+        self.block_entities.keys().copied().collect()
+    }
+
+    pub fn heightmaps(&self) -> Option<&Heightmaps> {
+        self.heightmaps.as_ref()
+    }
+
+    pub fn set_heightmaps(&mut self, heightmaps: Option<Heightmaps>) {
+        self.heightmaps = heightmaps;
+    }
+
+    pub fn take_heightmaps(&mut self) -> Option<Heightmaps> {
+        self.heightmaps.take()
+    }
+
+    // the compacted palette, bit width and packed indices, used by the save format
+    pub fn to_raw_parts(&self) -> (Vec<Block>, u8, Vec<u64>) {
+        // the block entities aren't part of this, so they aren't cloned
+        let mut chunk = Self {
+            palette: self.palette.clone(),
+            counts: self.counts.clone(),
+            bits_per_block: self.bits_per_block,
+            data: self.data.clone(),
+            block_entities: HashMap::new(),
+            heightmaps: None
+        };
+        chunk.compact();
+        (chunk.palette, chunk.bits_per_block, chunk.data)
+    }
+
+    // rebuilds a chunk from to_raw_parts, none when the parts don't fit together
+    pub fn from_raw_parts(palette: Vec<Block>, bits_per_block: u8, data: Vec<u64>) -> Option<Self> {
+        if palette.is_empty() || bits_per_block > 16 || bits_per_block < Self::bits_for(palette.len()) {
+            return None;
+        }
+        if data.len() != Self::words_for(bits_per_block) {
+            return None;
+        }
+
+        let mut chunk = Self {
+            counts: vec![0; palette.len()],
+            palette,
+            bits_per_block,
+            data,
+            block_entities: HashMap::new(),
+            heightmaps: None
+        };
+        for index in 0..CHUNK_LENGTH {
+            let palette_index = chunk.palette_index(index);
+            if palette_index >= chunk.palette.len() {
+                return None;
+            }
+            chunk.counts[palette_index] += 1;
+        }
+        Some(chunk)
+    }
+
+    // drops unused and duplicate palette entries and shrinks the index data to match
+    pub fn compact(&mut self) {
+        let block_entities = std::mem::take(&mut self.block_entities);
+        let heightmaps = self.heightmaps.take();
+        let blocks = self.to_blocks();
+        let mut palette: Vec<Block> = Vec::new();
+        for block in &blocks {
+            if !palette.contains(block) {
+                palette.push(*block);
             }
         }
+        // counted from the blocks, two palette entries may hold the same block
+        if palette.len() == 1 {
+            self.fill(palette[0]);
+            self.block_entities = block_entities;
+            self.heightmaps = heightmaps;
+            return;
+        }
+
+        let bits_per_block = Self::bits_for(palette.len());
+        let mut chunk = Self {
+            counts: vec![0; palette.len()],
+            palette,
+            bits_per_block,
+            data: vec![0; Self::words_for(bits_per_block)],
+            block_entities,
+            heightmaps
+        };
+        for (index, block) in blocks.iter().enumerate() {
+            let palette_index = chunk.palette.iter().position(|entry| entry == block).unwrap();
+            chunk.write_palette_index(index, palette_index);
+            chunk.counts[palette_index] += 1;
+        }
+        *self = chunk;
     }
 
-    return (vertices, normals, colors, uvs, vertices_transparent, normals_transparent, colors_transparent, uvs_transparent);
-}
\ No newline at end of file
+    fn palette_index(&self, index: usize) -> usize {
+        if self.bits_per_block == 0 {
+            return 0;
+        }
+        let per_word = 64 / self.bits_per_block as usize;
+        let shift = (index % per_word) * self.bits_per_block as usize;
+        let mask = (1u64 << self.bits_per_block) - 1;
+        ((self.data[index / per_word] >> shift) & mask) as usize
+    }
+
+    fn write_palette_index(&mut self, index: usize, palette_index: usize) {
+        let per_word = 64 / self.bits_per_block as usize;
+        let shift = (index % per_word) * self.bits_per_block as usize;
+        let mask = (1u64 << self.bits_per_block) - 1;
+        let word = &mut self.data[index / per_word];
+        *word = (*word & !(mask << shift)) | ((palette_index as u64) << shift);
+    }
+
+    // finds or adds the palette entry for a block, growing the index data when needed
+    fn palette_entry(&mut self, block: Block) -> usize {
+        if let Some(palette_index) = self.palette.iter().position(|entry| *entry == block) {
+            return palette_index;
+        }
+        if let Some(palette_index) = self.counts.iter().position(|count| *count == 0) {
+            self.palette[palette_index] = block;
+            return palette_index;
+        }
+
+        self.palette.push(block);
+        self.counts.push(0);
+        let bits_per_block = Self::bits_for(self.palette.len());
+        if bits_per_block != self.bits_per_block {
+            self.resize(bits_per_block);
+        }
+        self.palette.len() - 1
+    }
+
+    fn resize(&mut self, bits_per_block: u8) {
+        let old_indices: Vec<usize> = (0..CHUNK_LENGTH).map(|index| self.palette_index(index)).collect();
+        self.bits_per_block = bits_per_block;
+        self.data = vec![0; Self::words_for(bits_per_block)];
+        for (index, palette_index) in old_indices.into_iter().enumerate() {
+            self.write_palette_index(index, palette_index);
+        }
+    }
+
+    fn bits_for(palette_length: usize) -> u8 {
+        if palette_length <= 1 {
+            return 0;
+        }
+        (usize::BITS - (palette_length - 1).leading_zeros()) as u8
+    }
+
+    // how many words the packed indices take at this bit width
+    pub fn words_for(bits_per_block: u8) -> usize {
+        if bits_per_block == 0 {
+            return 0;
+        }
+        let per_word = 64 / bits_per_block as usize;
+        CHUNK_LENGTH.div_ceil(per_word)
+    }
+}
+impl Default for Chunk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,273 @@
+use std::borrow::Cow;
+
+use crate::world::blocks::{BlockRegistry, ShapeElement, FACE_NORMALS};
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition};
+use crate::world::falling_blocks::FallingBlock;
+use crate::world::fluids::fluid_height;
+use crate::world::light::{LightChannel, MAX_LIGHT};
+
+// vertices, normals, colors, uvs and sky light, ready for renderer::vertex::create_lit_vertices.
+// the colour is the block light, sky light is the level and ambient occlusion
+// of every vertex so the renderer can make it brighter or darker with the time of day
+pub type MeshData = (Vec<[f64; 3]>, Vec<[i8; 3]>, Vec<[f32; 3]>, Vec<[f32; 2]>, Vec<[f32; 2]>);
+
+// position, colour, uv and sky light of one corner of a face
+type Corner = ([f64; 3], [f32; 3], [f32; 2], [f32; 2]);
+
+const ATLAS_WIDTH: f32 = 8.0;
+const ATLAS_HEIGHT: f32 = 8.0;
+
+// corners of every face of the unit cube in FACE_NORMALS order, counter
+// clockwise when looking at the face: bottom left, bottom right, top right, top left
+const FACE_CORNERS: [[[f32; 3]; 4]; 6] = [
+    [[1.0, 0.0, 1.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [1.0, 1.0, 1.0]],
+    [[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 1.0], [0.0, 1.0, 0.0]],
+    [[0.0, 1.0, 1.0], [1.0, 1.0, 1.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+    [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 1.0], [0.0, 0.0, 1.0]],
+    [[0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [1.0, 1.0, 1.0], [0.0, 1.0, 1.0]],
+    [[1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]]
+];
+const CORNER_UVS: [[f32; 2]; 4] = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+// two triangles per face, the same order the old renderer used
+const FACE_TRIANGLES: [usize; 6] = [0, 1, 3, 3, 1, 2];
+// how bright a corner is depending on how many blocks surround it
+const AMBIENT_OCCLUSION: [f32; 4] = [0.5, 0.65, 0.8, 1.0];
+const MIN_BRIGHTNESS: f32 = 0.05;
+
+// builds the mesh of one chunk, blocks in neighbouring chunks are used to
+// hide faces on the border so every chunk has to be remeshed when a neighbour loads
+pub fn render_chunk(chunks: &ChunkMap, blocks: &BlockRegistry, position: ChunkPosition) -> MeshData {
+    let mut vertices: Vec<[f64; 3]> = Vec::new();
+    let mut normals: Vec<[i8; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut sky: Vec<[f32; 2]> = Vec::new();
+
+    let Some(chunk) = chunks.get_chunk(position) else {
+        return (vertices, normals, colors, uvs, sky);
+    };
+
+    for (local_position, chunk_block) in chunk.iter_solid() {
+        let Some(block) = blocks.get(chunk_block.id) else { continue; };
+        let Some(shape) = blocks.get_shape(&block.shape) else { continue; };
+        // the state turns the whole block, textures move with their faces
+        let orientation = blocks.orientation(chunk_block);
+        let textures = orientation.apply_faces(block.textures.faces());
+        let block_position = ChunkMap::world_position(position, local_position);
+        let layers = blocks.layers(chunk_block);
+        // flowing fluid is drawn as high as fluid_at lets players swim in it
+        let fluid_height = fluid_height(blocks, chunk_block);
+
+        for element in &shape.elements {
+            let mut element = if orientation.is_identity() && layers == 1 {
+                Cow::Borrowed(element)
+            } else {
+                Cow::Owned(orientation.apply_element(&element.stacked(layers)))
+            };
+            if fluid_height < 1.0 {
+                element.to_mut().to[1] = element.to[1].min(fluid_height * 16.0);
+            }
+            let element = element.as_ref();
+            for face in 0..6 {
+                if !has_area(element, face) {
+                    continue;
+                }
+                let normal = FACE_NORMALS[face];
+                // faces on the border are lit by the block in front, the others by the space inside the block
+                let mut lit_from = block_position;
+                if on_block_border(element, face) {
+                    lit_from = offset(block_position, normal, 1);
+                    let neighbour = chunks.get_block(lit_from);
+                    // faces facing chunks that aren't loaded yet stay hidden until they are
+                    if neighbour.is_none_or(|neighbour| blocks.occludes(neighbour)) {
+                        continue;
+                    }
+                    // fluids only show their surface, not the faces between their blocks
+                    if neighbour.is_some_and(|neighbour| neighbour.id == chunk_block.id && block.fluid.is_some()) {
+                        continue;
+                    }
+                }
+                let light = block_light_color(chunks, lit_from);
+                let sky_light = sky_light(chunks, lit_from);
+
+                let uv_x = (textures[face] as f32 % ATLAS_WIDTH).floor();
+                let uv_y = (textures[face] as f32 / ATLAS_HEIGHT).floor();
+
+                let mut corners: [Corner; 4] = [([0.0; 3], [0.0; 3], [0.0; 2], [0.0; 2]); 4];
+                for (corner, unit) in FACE_CORNERS[face].iter().enumerate() {
+                    let point = corner_point(element, *unit);
+                    let vertex = [
+                        block_position.0 as f64 * 2.0 - 1.0 + point[0] as f64 / 8.0,
+                        block_position.1 as f64 * 2.0 - 1.0 + point[1] as f64 / 8.0,
+                        block_position.2 as f64 * 2.0 - 1.0 + point[2] as f64 / 8.0
+                    ];
+                    let occlusion = AMBIENT_OCCLUSION[ambient_occlusion(chunks, blocks, block_position, face, *unit)];
+                    let color = light.map(|channel| channel * occlusion);
+                    let uv = [
+                        (CORNER_UVS[corner][0] + uv_x) / ATLAS_WIDTH,
+                        (CORNER_UVS[corner][1] + uv_y) / ATLAS_HEIGHT
+                    ];
+                    corners[corner] = (vertex, color, uv, [sky_light, occlusion]);
+                }
+
+                for corner in FACE_TRIANGLES {
+                    let (vertex, color, uv, corner_sky) = corners[corner];
+                    vertices.push(vertex);
+                    normals.push([normal.0 as i8, normal.1 as i8, normal.2 as i8]);
+                    colors.push(color);
+                    uvs.push(uv);
+                    sky.push(corner_sky);
+                }
+            }
+        }
+    }
+
+    (vertices, normals, colors, uvs, sky)
+}
+
+// falling blocks are drawn whole at their position, lit by the block they are in
+pub fn render_falling_blocks(chunks: &ChunkMap, blocks: &BlockRegistry, falling_blocks: &[FallingBlock]) -> MeshData {
+    let mut vertices: Vec<[f64; 3]> = Vec::new();
+    let mut normals: Vec<[i8; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut sky: Vec<[f32; 2]> = Vec::new();
+
+    for falling in falling_blocks {
+        let Some(block) = blocks.get(falling.block.id) else { continue; };
+        let Some(shape) = blocks.get_shape(&block.shape) else { continue; };
+        let orientation = blocks.orientation(falling.block);
+        let textures = orientation.apply_faces(block.textures.faces());
+        let position = falling.position;
+        let lit_from = ChunkMap::block_at_point(position.0 as f32, position.1 as f32, position.2 as f32);
+        let light = block_light_color(chunks, lit_from);
+        let sky_light = sky_light(chunks, lit_from);
+        let layers = blocks.layers(falling.block);
+
+        for element in &shape.elements {
+            let element = orientation.apply_element(&element.stacked(layers));
+            for face in 0..6 {
+                if !has_area(&element, face) {
+                    continue;
+                }
+                let normal = FACE_NORMALS[face];
+                let uv_x = (textures[face] as f32 % ATLAS_WIDTH).floor();
+                let uv_y = (textures[face] as f32 / ATLAS_HEIGHT).floor();
+                for corner in FACE_TRIANGLES {
+                    let point = corner_point(&element, FACE_CORNERS[face][corner]);
+                    vertices.push([
+                        position.0 * 2.0 - 1.0 + point[0] as f64 / 8.0,
+                        position.1 * 2.0 - 1.0 + point[1] as f64 / 8.0,
+                        position.2 * 2.0 - 1.0 + point[2] as f64 / 8.0
+                    ]);
+                    normals.push([normal.0 as i8, normal.1 as i8, normal.2 as i8]);
+                    colors.push(light);
+                    sky.push([sky_light, 1.0]);
+                    uvs.push([
+                        (CORNER_UVS[corner][0] + uv_x) / ATLAS_WIDTH,
+                        (CORNER_UVS[corner][1] + uv_y) / ATLAS_HEIGHT
+                    ]);
+                }
+            }
+        }
+    }
+
+    (vertices, normals, colors, uvs, sky)
+}
+
+// a corner of the element, unit is 0 or 1 on every axis
+fn corner_point(element: &ShapeElement, unit: [f32; 3]) -> [f32; 3] {
+    rotate(element, [
+        element.from[0] + (element.to[0] - element.from[0]) * unit[0],
+        element.from[1] + (element.to[1] - element.from[1]) * unit[1],
+        element.from[2] + (element.to[2] - element.from[2]) * unit[2]
+    ])
+}
+
+// the brightness of every colour channel of the block light. sky light is
+// white and added by the shader, it takes the brighter of the two for every
+// channel. even the darkest caves aren't fully black
+fn block_light_color(chunks: &ChunkMap, position: BlockPosition) -> [f32; 3] {
+    LightChannel::BLOCK.map(|channel| {
+        let level = chunks.get_light(position, channel).unwrap_or(0);
+        MIN_BRIGHTNESS + (1.0 - MIN_BRIGHTNESS) * (level as f32 / MAX_LIGHT as f32).powf(1.5)
+    })
+}
+
+// from 0 to 1, how bright it is depends on the time of day so the shader does that part
+fn sky_light(chunks: &ChunkMap, position: BlockPosition) -> f32 {
+    chunks.get_light(position, LightChannel::Sky).unwrap_or(MAX_LIGHT) as f32 / MAX_LIGHT as f32
+}
+
+fn offset(position: BlockPosition, direction: (i64, i64, i64), distance: i64) -> BlockPosition {
+    (position.0 + direction.0 * distance, position.1 + direction.1 * distance, position.2 + direction.2 * distance)
+}
+
+fn face_axis(face: usize) -> usize {
+    face / 2
+}
+
+// flat elements like grass only have faces along their flat side
+fn has_area(element: &ShapeElement, face: usize) -> bool {
+    let axis = face_axis(face);
+    (0..3).filter(|other| *other != axis).all(|other| element.from[other] != element.to[other])
+}
+
+fn on_block_border(element: &ShapeElement, face: usize) -> bool {
+    if element.rotation.as_ref().is_some_and(|rotation| rotation.angle != 0.0) {
+        return false;
+    }
+    let axis = face_axis(face);
+    if FACE_NORMALS[face].0 + FACE_NORMALS[face].1 + FACE_NORMALS[face].2 > 0 {
+        element.to[axis] >= 16.0
+    } else {
+        element.from[axis] <= 0.0
+    }
+}
+
+fn rotate(element: &ShapeElement, point: [f32; 3]) -> [f32; 3] {
+    let Some(rotation) = &element.rotation else {
+        return point;
+    };
+    if rotation.angle == 0.0 {
+        return point;
+    }
+
+    let (sin, cos) = rotation.angle.to_radians().sin_cos();
+    let origin = rotation.origin;
+    let relative = [point[0] - origin[0], point[1] - origin[1], point[2] - origin[2]];
+    let rotated = match rotation.axis.as_str() {
+        "x" => [relative[0], relative[1] * cos - relative[2] * sin, relative[1] * sin + relative[2] * cos],
+        "y" => [relative[0] * cos + relative[2] * sin, relative[1], -relative[0] * sin + relative[2] * cos],
+        _ => [relative[0] * cos - relative[1] * sin, relative[0] * sin + relative[1] * cos, relative[2]]
+    };
+    [rotated[0] + origin[0], rotated[1] + origin[1], rotated[2] + origin[2]]
+}
+
+// counts the blocks touching a corner in front of the face, 3 means nothing
+// is in the way and 0 means the corner sits in a crease
+fn ambient_occlusion(chunks: &ChunkMap, blocks: &BlockRegistry, block_position: BlockPosition, face: usize, corner: [f32; 3]) -> usize {
+    let axis = face_axis(face);
+    let front = offset(block_position, FACE_NORMALS[face], 1);
+    let tangents: Vec<usize> = (0..3).filter(|other| *other != axis).collect();
+
+    let mut directions = [(0, 0, 0); 2];
+    for (index, tangent) in tangents.iter().enumerate() {
+        let sign = if corner[*tangent] > 0.5 { 1 } else { -1 };
+        directions[index] = match tangent {
+            0 => (sign, 0, 0),
+            1 => (0, sign, 0),
+            _ => (0, 0, sign)
+        };
+    }
+
+    let solid = |position: BlockPosition| chunks.get_block(position).is_some_and(|block| blocks.occludes(block));
+    let side_1 = solid(offset(front, directions[0], 1));
+    let side_2 = solid(offset(front, directions[1], 1));
+    let corner = solid(offset(offset(front, directions[0], 1), directions[1], 1));
+
+    if side_1 && side_2 {
+        return 0;
+    }
+    3 - side_1 as usize - side_2 as usize - corner as usize
+}
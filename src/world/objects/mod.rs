@@ -1 +1,3 @@
-pub mod cube;
\ No newline at end of file
+pub mod cube;
+pub mod chunk;
+pub mod chunk_mesh;
\ No newline at end of file
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+use crate::config::CHUNK_LENGTH;
+use crate::world::blocks::BlockRegistry;
+use crate::world::chunk_map::{BlockPosition, ChunkMap, NEIGHBOUR_OFFSETS};
+use crate::world::light::LightChannel;
+use crate::world::objects::chunk::{Block, Chunk};
+use crate::world::world::World;
+
+// something a block does when it is picked by a random tick, blocks list
+// theirs under "random_ticks" in their json. everything besides the handler
+// and the chance is up to the handler, like "into" for convert
+#[derive(Deserialize, Clone, Debug)]
+pub struct RandomTickDef {
+    pub handler: String,
+    // how likely it is to run when the block is picked
+    #[serde(default = "always")]
+    pub chance: f32,
+    #[serde(flatten)]
+    pub options: HashMap<String, serde_json::Value>
+}
+impl RandomTickDef {
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.options.get(name).and_then(|value| value.as_str())
+    }
+
+    pub fn get_u64(&self, name: &str) -> Option<u64> {
+        self.options.get(name).and_then(|value| value.as_u64())
+    }
+
+    // a list of strings, a single string counts as a list of one
+    pub fn get_list(&self, name: &str) -> Vec<&str> {
+        match self.options.get(name) {
+            Some(serde_json::Value::String(value)) => vec![value.as_str()],
+            Some(serde_json::Value::Array(values)) => values.iter().filter_map(|value| value.as_str()).collect(),
+            _ => Vec::new()
+        }
+    }
+}
+
+fn always() -> f32 {
+    1.0
+}
+
+// runs one random tick of the block at the position
+pub type RandomTickHandler = fn(&mut World, BlockPosition, Block, &RandomTickDef);
+
+// every handler a random tick can name, mods add theirs with register
+pub struct RandomTickHandlers {
+    handlers: HashMap<String, RandomTickHandler>
+}
+impl RandomTickHandlers {
+    pub fn new() -> Self {
+        let mut handlers = Self {
+            handlers: HashMap::new()
+        };
+        handlers.register("convert", convert);
+        handlers
+    }
+
+    pub fn register(&mut self, name: &str, handler: RandomTickHandler) {
+        self.handlers.insert(name.to_string(), handler);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<RandomTickHandler> {
+        self.handlers.get(name).copied()
+    }
+}
+impl Default for RandomTickHandlers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// count random blocks of every loaded chunk that has something that ticks,
+// most chunks are only air and stone so they are skipped without looking
+pub fn pick_blocks(chunks: &ChunkMap, blocks: &BlockRegistry, rng: &mut ChaCha8Rng, count: usize) -> Vec<(BlockPosition, Block)> {
+    let mut picked = Vec::new();
+    for (position, chunk) in chunks.iter() {
+        if chunk.is_empty() || !chunk.palette().any(|block| blocks.has_random_ticks(block)) {
+            continue;
+        }
+        for _ in 0..count {
+            let local_position = Chunk::position(rng.gen_range(0..CHUNK_LENGTH));
+            let block = chunk.get(local_position.0, local_position.1, local_position.2);
+            if blocks.has_random_ticks(block) {
+                picked.push((ChunkMap::world_position(*position, local_position), block));
+            }
+        }
+    }
+    picked
+}
+
+// runs the random ticks of a picked block, handlers that aren't registered
+// (a mod was removed) are skipped
+pub fn random_tick(world: &mut World, position: BlockPosition, block: Block) {
+    let Some(ticks) = world.get_blocks().get(block.id).map(|definition| definition.random_ticks.clone()) else {
+        return;
+    };
+    for tick in ticks {
+        // an earlier tick may have replaced the block already
+        if world.get_block(position) != Some(block) {
+            return;
+        }
+        let Some(handler) = world.get_random_tick_handlers().get(&tick.handler) else {
+            continue;
+        };
+        if tick.chance < 1.0 && world.get_rng_mut().gen::<f32>() >= tick.chance {
+            continue;
+        }
+        handler(world, position, block, &tick);
+    }
+}
+
+// turns the block into "into" when its surroundings fit:
+// "near" lists blocks one of which has to be within "near_radius" (1),
+// "not_near" lists blocks none of which may be within "not_near_radius" (1),
+// "min_light" and "max_light" bound the brightest light next to it and
+// "min_block_light" the brightest light that isn't from the sky
+fn convert(world: &mut World, position: BlockPosition, _block: Block, tick: &RandomTickDef) {
+    let Some(into) = tick.get_str("into").and_then(|into| world.get_blocks().parse_block(into)) else {
+        return;
+    };
+
+    // when part of the cube isn't loaded the block waits, a leaf at the edge
+    // of the loaded area may belong to a tree in the next chunk
+    let near = tick.get_list("near");
+    if !near.is_empty() && is_near(world, position, &near, tick.get_u64("near_radius").unwrap_or(1) as i64) != Some(true) {
+        return;
+    }
+    let not_near = tick.get_list("not_near");
+    if !not_near.is_empty() && is_near(world, position, &not_near, tick.get_u64("not_near_radius").unwrap_or(1) as i64) != Some(false) {
+        return;
+    }
+
+    let light = neighbour_light(world, position, &LightChannel::ALL);
+    if tick.get_u64("min_light").is_some_and(|min| (light as u64) < min) {
+        return;
+    }
+    if tick.get_u64("max_light").is_some_and(|max| light as u64 > max) {
+        return;
+    }
+    let block_light = neighbour_light(world, position, &LightChannel::BLOCK);
+    if tick.get_u64("min_block_light").is_some_and(|min| (block_light as u64) < min) {
+        return;
+    }
+
+    world.set_block(position, into);
+}
+
+// whether one of the named blocks is in the cube around the position, none
+// when none of the loaded blocks is but some of the cube isn't loaded
+pub fn is_near(world: &World, position: BlockPosition, names: &[&str], radius: i64) -> Option<bool> {
+    let blocks = world.get_blocks();
+    let mut unloaded = false;
+    for x in -radius..=radius {
+        for y in -radius..=radius {
+            for z in -radius..=radius {
+                if (x, y, z) == (0, 0, 0) {
+                    continue;
+                }
+                let Some(block) = world.get_block((position.0 + x, position.1 + y, position.2 + z)) else {
+                    unloaded = true;
+                    continue;
+                };
+                if block.is_air() {
+                    continue;
+                }
+                let Some(definition) = blocks.get(block.id) else {
+                    continue;
+                };
+                if names.iter().any(|name| *name == definition.name || *name == definition.full_name()) {
+                    return Some(true);
+                }
+            }
+        }
+    }
+    (!unloaded).then_some(false)
+}
+
+// the brightest of the channels in the blocks next to the position, a solid
+// block has no light of its own so its neighbours are what lights it
+pub fn neighbour_light(world: &World, position: BlockPosition, channels: &[LightChannel]) -> u8 {
+    let chunks = world.get_chunks();
+    NEIGHBOUR_OFFSETS.iter()
+        .map(|offset| (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2))
+        .flat_map(|neighbour| channels.iter().filter_map(move |channel| chunks.get_light(neighbour, *channel)))
+        .max()
+        .unwrap_or(0)
+}
+
@@ -0,0 +1,320 @@
+use std::io::{self, Read};
+
+use crate::world::block_entities::BlockEntityKinds;
+use crate::world::block_updates::{SavedUpdate, UpdateReason};
+use crate::world::falling_blocks::FallingBlock;
+use crate::world::generation::structure_placement::PendingBlocks;
+use crate::world::heightmaps::Heightmaps;
+use crate::world::objects::chunk::{Block, Chunk};
+
+// bumped whenever the layout of a saved chunk changes
+pub const CHUNK_FORMAT_VERSION: u8 = 2;
+// the files next to the regions have their own versions, they started out
+// with the chunk version and only change when their own layout does
+pub const PENDING_BLOCKS_VERSION: u8 = 2;
+pub const SCHEDULED_UPDATES_VERSION: u8 = 2;
+pub const FALLING_BLOCKS_VERSION: u8 = 2;
+
+// a saved chunk is a version byte followed by tagged sections, unknown
+// sections are skipped so adding a new one doesn't break older saves
+const SECTION_BLOCKS: u8 = 1;
+const SECTION_BLOCK_ENTITIES: u8 = 2;
+const SECTION_HEIGHTMAPS: u8 = 3;
+
+pub fn encode_chunk(chunk: &Chunk) -> Vec<u8> {
+    let mut sections: Vec<(u8, Vec<u8>)> = vec![
+        (SECTION_BLOCKS, encode_blocks(chunk))
+    ];
+    if chunk.block_entities().next().is_some() {
+        sections.push((SECTION_BLOCK_ENTITIES, encode_block_entities(chunk)));
+    }
+    if let Some(heightmaps) = chunk.heightmaps() {
+        sections.push((SECTION_HEIGHTMAPS, heightmaps.to_bytes().to_vec()));
+    }
+
+    let mut bytes = vec![CHUNK_FORMAT_VERSION, sections.len() as u8];
+    for (tag, section) in sections {
+        bytes.push(tag);
+        bytes.extend_from_slice(&(section.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&section);
+    }
+    bytes
+}
+
+// block entities are built through their kind, so decoding needs to know them
+pub fn decode_chunk(bytes: &[u8], block_entity_kinds: &BlockEntityKinds) -> io::Result<Chunk> {
+    let mut reader = bytes;
+    let version = read_u8(&mut reader)?;
+    if version == 0 || version > CHUNK_FORMAT_VERSION {
+        return Err(invalid_data(format!("unsupported chunk format version {}", version)));
+    }
+
+    let mut chunk = None;
+    let mut block_entities = None;
+    let mut heightmaps = None;
+    let section_count = read_u8(&mut reader)?;
+    for _ in 0..section_count {
+        let tag = read_u8(&mut reader)?;
+        let length = read_u32(&mut reader)? as usize;
+        if reader.len() < length {
+            return Err(invalid_data(format!("chunk section {} is truncated", tag)));
+        }
+        let (section, rest) = reader.split_at(length);
+        reader = rest;
+
+        match tag {
+            SECTION_BLOCKS => chunk = Some(decode_blocks(section, version)?),
+            SECTION_BLOCK_ENTITIES => block_entities = Some(section),
+            // broken heightmaps are simply computed again
+            SECTION_HEIGHTMAPS => heightmaps = Heightmaps::from_bytes(section),
+            _ => {}
+        }
+    }
+
+    let mut chunk = chunk.ok_or_else(|| invalid_data("chunk has no block section".to_string()))?;
+    if let Some(section) = block_entities {
+        decode_block_entities(section, &mut chunk, block_entity_kinds)?;
+    }
+    chunk.set_heightmaps(heightmaps);
+    Ok(chunk)
+}
+
+fn encode_blocks(chunk: &Chunk) -> Vec<u8> {
+    let (palette, bits_per_block, data) = chunk.to_raw_parts();
+
+    let mut bytes = Vec::with_capacity(3 + palette.len() * 4 + 4 + data.len() * 8);
+    bytes.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+    for block in &palette {
+        bytes.extend_from_slice(&block.id.to_le_bytes());
+        bytes.extend_from_slice(&block.state.to_le_bytes());
+    }
+    bytes.push(bits_per_block);
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    for word in &data {
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
+fn decode_blocks(mut reader: &[u8], version: u8) -> io::Result<Chunk> {
+    // version 1 had no block states
+    let block_size = if version >= 2 { 4 } else { 2 };
+    let palette_length = read_u16(&mut reader)? as usize;
+    let palette_length = read_count(reader, palette_length, block_size, "palette")?;
+    let mut palette: Vec<Block> = Vec::with_capacity(palette_length);
+    for _ in 0..palette_length {
+        let id = read_u16(&mut reader)?;
+        let state = if version >= 2 { read_u16(&mut reader)? } else { 0 };
+        palette.push(Block::new(id, state));
+    }
+    let bits_per_block = read_u8(&mut reader)?;
+    let data_length = read_u32(&mut reader)? as usize;
+    if bits_per_block > 16 || data_length != Chunk::words_for(bits_per_block) {
+        return Err(invalid_data(format!("chunk has {} words of block data for {} bits per block", data_length, bits_per_block)));
+    }
+    let mut data: Vec<u64> = Vec::with_capacity(read_count(reader, data_length, 8, "block data")?);
+    for _ in 0..data_length {
+        data.push(read_u64(&mut reader)?);
+    }
+
+    Chunk::from_raw_parts(palette, bits_per_block, data)
+        .ok_or_else(|| invalid_data("chunk block data does not match its palette".to_string()))
+}
+
+// every block entity is its position, kind and the json it saved itself as
+fn encode_block_entities(chunk: &Chunk) -> Vec<u8> {
+    let block_entities: Vec<_> = chunk.block_entities().collect();
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(block_entities.len() as u32).to_le_bytes());
+    for ((x, y, z), block_entity) in block_entities {
+        let kind = block_entity.kind().as_bytes();
+        let data = block_entity.save().to_string().into_bytes();
+        bytes.extend_from_slice(&[x as u8, y as u8, z as u8]);
+        bytes.extend_from_slice(&(kind.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(kind);
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&data);
+    }
+    bytes
+}
+
+fn decode_block_entities(mut reader: &[u8], chunk: &mut Chunk, block_entity_kinds: &BlockEntityKinds) -> io::Result<()> {
+    let count = read_u32(&mut reader)?;
+    for _ in 0..count {
+        let (x, y, z) = (read_u8(&mut reader)?, read_u8(&mut reader)?, read_u8(&mut reader)?);
+        if !Chunk::in_bounds(x as i64, y as i64, z as i64) {
+            return Err(invalid_data(format!("block entity at {} {} {} is outside the chunk", x, y, z)));
+        }
+        let kind_length = read_u16(&mut reader)? as usize;
+        let kind = read_string(&mut reader, kind_length)?;
+        let data_length = read_u32(&mut reader)? as usize;
+        let data = read_string(&mut reader, data_length)?;
+        let data: serde_json::Value = serde_json::from_str(&data)
+            .map_err(|err| invalid_data(format!("block entity {} has invalid data: {}", kind, err)))?;
+        chunk.set_block_entity(x as usize, y as usize, z as usize, block_entity_kinds.load(&kind, data));
+    }
+    Ok(())
+}
+
+// structure blocks for chunks that don't exist yet, saved next to the regions
+pub fn encode_pending_blocks(pending: &PendingBlocks) -> Vec<u8> {
+    let mut bytes = vec![PENDING_BLOCKS_VERSION];
+    bytes.extend_from_slice(&(pending.len() as u32).to_le_bytes());
+    for (position, blocks) in pending.iter() {
+        for value in [position.0, position.1, position.2] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(blocks.len() as u32).to_le_bytes());
+        for ((x, y, z), block) in blocks {
+            bytes.extend_from_slice(&[*x as u8, *y as u8, *z as u8]);
+            bytes.extend_from_slice(&block.id.to_le_bytes());
+            bytes.extend_from_slice(&block.state.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+pub fn decode_pending_blocks(mut reader: &[u8]) -> io::Result<PendingBlocks> {
+    let version = read_u8(&mut reader)?;
+    if version == 0 || version > PENDING_BLOCKS_VERSION {
+        return Err(invalid_data(format!("unsupported pending blocks version {}", version)));
+    }
+
+    let mut pending = PendingBlocks::new();
+    // a chunk is its position and block count, a block its position, id and state
+    let chunk_count = read_u32(&mut reader)? as usize;
+    let chunk_count = read_count(reader, chunk_count, 3 * 8 + 4, "pending chunk")?;
+    for _ in 0..chunk_count {
+        let position = (read_u64(&mut reader)? as i64, read_u64(&mut reader)? as i64, read_u64(&mut reader)? as i64);
+        let block_count = read_u32(&mut reader)? as usize;
+        let block_count = read_count(reader, block_count, 3 + 2 + 2, "pending block")?;
+        let mut blocks = Vec::with_capacity(block_count);
+        for _ in 0..block_count {
+            let (x, y, z) = (read_u8(&mut reader)?, read_u8(&mut reader)?, read_u8(&mut reader)?);
+            if !Chunk::in_bounds(x as i64, y as i64, z as i64) {
+                return Err(invalid_data(format!("pending block at {} {} {} is outside its chunk", x, y, z)));
+            }
+            let block = Block::new(read_u16(&mut reader)?, read_u16(&mut reader)?);
+            blocks.push(((x as usize, y as usize, z as usize), block));
+        }
+        pending.add_to_chunk(position, blocks);
+    }
+    Ok(pending)
+}
+
+// block updates that were still waiting, with the ticks they had left
+pub fn encode_scheduled_updates(updates: &[SavedUpdate]) -> Vec<u8> {
+    let mut bytes = vec![SCHEDULED_UPDATES_VERSION];
+    bytes.extend_from_slice(&(updates.len() as u32).to_le_bytes());
+    for (position, delay, reason) in updates {
+        for value in [position.0, position.1, position.2] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&delay.to_le_bytes());
+        bytes.push(reason.to_byte());
+    }
+    bytes
+}
+
+pub fn decode_scheduled_updates(mut reader: &[u8]) -> io::Result<Vec<SavedUpdate>> {
+    let version = read_u8(&mut reader)?;
+    if version == 0 || version > SCHEDULED_UPDATES_VERSION {
+        return Err(invalid_data(format!("unsupported scheduled updates version {}", version)));
+    }
+
+    // a position, the delay and the reason
+    let count = read_u32(&mut reader)? as usize;
+    let count = read_count(reader, count, 3 * 8 + 8 + 1, "scheduled update")?;
+    let mut updates = Vec::with_capacity(count);
+    for _ in 0..count {
+        let position = (read_u64(&mut reader)? as i64, read_u64(&mut reader)? as i64, read_u64(&mut reader)? as i64);
+        let delay = read_u64(&mut reader)?;
+        let reason = read_u8(&mut reader)?;
+        let reason = UpdateReason::from_byte(reason)
+            .ok_or_else(|| invalid_data(format!("unknown block update reason {}", reason)))?;
+        updates.push((position, delay, reason));
+    }
+    Ok(updates)
+}
+
+// blocks that were falling when the world was saved
+pub fn encode_falling_blocks(falling_blocks: &[FallingBlock]) -> Vec<u8> {
+    let mut bytes = vec![FALLING_BLOCKS_VERSION];
+    bytes.extend_from_slice(&(falling_blocks.len() as u32).to_le_bytes());
+    for falling in falling_blocks {
+        bytes.extend_from_slice(&falling.block.id.to_le_bytes());
+        bytes.extend_from_slice(&falling.block.state.to_le_bytes());
+        for value in [falling.position.0, falling.position.1, falling.position.2, falling.velocity] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+pub fn decode_falling_blocks(mut reader: &[u8]) -> io::Result<Vec<FallingBlock>> {
+    let version = read_u8(&mut reader)?;
+    if version == 0 || version > FALLING_BLOCKS_VERSION {
+        return Err(invalid_data(format!("unsupported falling blocks version {}", version)));
+    }
+
+    // the block, its position and velocity
+    let count = read_u32(&mut reader)? as usize;
+    let count = read_count(reader, count, 2 + 2 + 4 * 8, "falling block")?;
+    let mut falling_blocks = Vec::with_capacity(count);
+    for _ in 0..count {
+        let block = Block::new(read_u16(&mut reader)?, read_u16(&mut reader)?);
+        let position = (read_f64(&mut reader)?, read_f64(&mut reader)?, read_f64(&mut reader)?);
+        let velocity = read_f64(&mut reader)?;
+        falling_blocks.push(FallingBlock { block, position, velocity });
+    }
+    Ok(falling_blocks)
+}
+
+pub fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// a count read from a file, checked against the bytes that are left so a
+// corrupt file fails to load instead of allocating gigabytes
+pub fn read_count(reader: &[u8], count: usize, element_size: usize, what: &str) -> io::Result<usize> {
+    if count.saturating_mul(element_size) > reader.len() {
+        return Err(invalid_data(format!("{} count {} is larger than the data that is left", what, count)));
+    }
+    Ok(count)
+}
+
+pub fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+pub fn read_u16(reader: &mut impl Read) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+pub fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub fn read_f64(reader: &mut impl Read) -> io::Result<f64> {
+    Ok(f64::from_bits(read_u64(reader)?))
+}
+
+pub fn read_string(reader: &mut &[u8], length: usize) -> io::Result<String> {
+    let mut bytes = vec![0; read_count(reader, length, 1, "string length")?];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|err| invalid_data(err.to_string()))
+}
+
@@ -0,0 +1,3 @@
+pub mod world_storage;
+pub mod region;
+pub mod chunk_format;
\ No newline at end of file
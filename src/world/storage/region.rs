@@ -0,0 +1,274 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use flate2::Compression as DeflateLevel;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+
+use crate::world::chunk_map::ChunkPosition;
+use crate::world::storage::chunk_format::{invalid_data, read_u16, read_u32, read_u8};
+
+pub type RegionPosition = (i64, i64, i64);
+
+// a region file holds REGION_SIZE^3 chunks
+pub const REGION_SIZE: i64 = 16;
+pub const REGION_VERSION: u16 = 1;
+
+const REGION_MAGIC: &[u8; 4] = b"PDRG";
+const REGION_CHUNKS: usize = (REGION_SIZE * REGION_SIZE * REGION_SIZE) as usize;
+
+// the file is split into sectors, the first one is the header, followed by
+// the offset table and then the chunk data
+const SECTOR_SIZE: u64 = 4096;
+const ENTRY_SIZE: u64 = 8;
+const TABLE_SECTORS: u32 = ((REGION_CHUNKS as u64 * ENTRY_SIZE) / SECTOR_SIZE) as u32;
+const DATA_START_SECTOR: u32 = 1 + TABLE_SECTORS;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Compression {
+    None = 0,
+    Deflate = 1
+}
+impl Compression {
+    fn from_id(id: u8) -> io::Result<Self> {
+        match id {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Deflate),
+            _ => Err(invalid_data(format!("unknown chunk compression {}", id)))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct RegionEntry {
+    sector: u32,
+    sector_count: u16,
+    compression: u8
+}
+impl RegionEntry {
+    fn is_empty(&self) -> bool {
+        self.sector_count == 0
+    }
+}
+
+pub struct RegionFile {
+    file: File,
+    entries: Vec<RegionEntry>,
+    // which sectors of the file are taken, so freed space can be reused
+    used_sectors: Vec<bool>
+}
+impl RegionFile {
+    pub fn region_position(chunk_position: ChunkPosition) -> RegionPosition {
+        (
+            chunk_position.0.div_euclid(REGION_SIZE),
+            chunk_position.1.div_euclid(REGION_SIZE),
+            chunk_position.2.div_euclid(REGION_SIZE)
+        )
+    }
+
+    pub fn file_name(region_position: RegionPosition) -> String {
+        format!("r.{}.{}.{}.region", region_position.0, region_position.1, region_position.2)
+    }
+
+    // opens a region file, creating an empty one when it doesn't exist yet
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let exists = path.exists();
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+
+        let mut region = Self {
+            file,
+            entries: vec![RegionEntry::default(); REGION_CHUNKS],
+            used_sectors: vec![true; DATA_START_SECTOR as usize]
+        };
+        if exists && region.file.metadata()?.len() > 0 {
+            region.read_header()?;
+        } else {
+            region.write_header()?;
+        }
+        Ok(region)
+    }
+
+    pub fn contains_chunk(&self, chunk_position: ChunkPosition) -> bool {
+        !self.entries[Self::entry_index(chunk_position)].is_empty()
+    }
+
+    pub fn read_chunk(&mut self, chunk_position: ChunkPosition) -> io::Result<Option<Vec<u8>>> {
+        let entry = self.entries[Self::entry_index(chunk_position)];
+        if entry.is_empty() {
+            return Ok(None);
+        }
+
+        self.file.seek(SeekFrom::Start(entry.sector as u64 * SECTOR_SIZE))?;
+        let length = read_u32(&mut self.file)? as usize;
+        if length as u64 + 4 > entry.sector_count as u64 * SECTOR_SIZE {
+            return Err(invalid_data(format!("chunk {:?} is longer than its sectors", chunk_position)));
+        }
+        let mut stored = vec![0; length];
+        self.file.read_exact(&mut stored)?;
+
+        let bytes = match Compression::from_id(entry.compression)? {
+            Compression::None => stored,
+            Compression::Deflate => {
+                let mut bytes = Vec::new();
+                ZlibDecoder::new(stored.as_slice()).read_to_end(&mut bytes)?;
+                bytes
+            }
+        };
+        Ok(Some(bytes))
+    }
+
+    pub fn write_chunk(&mut self, chunk_position: ChunkPosition, bytes: &[u8], compression: Compression) -> io::Result<()> {
+        let stored = match compression {
+            Compression::None => bytes.to_vec(),
+            Compression::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), DeflateLevel::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()?
+            }
+        };
+
+        let sector_count = (stored.len() as u64 + 4).div_ceil(SECTOR_SIZE);
+        if sector_count > u16::MAX as u64 {
+            return Err(invalid_data(format!("chunk {:?} is too large to save", chunk_position)));
+        }
+        let sector_count = sector_count as u16;
+
+        // the new copy goes into sectors the old one doesn't use and the old
+        // sectors are only freed once the entry points at the new ones, so a
+        // failed write never leaves the chunk without a whole copy
+        let index = Self::entry_index(chunk_position);
+        let old_entry = self.entries[index];
+        let sector = self.find_free_sectors(sector_count);
+        self.mark_sectors(sector, sector_count, true);
+        if let Err(err) = self.write_sectors(sector, sector_count, &stored) {
+            self.mark_sectors(sector, sector_count, false);
+            return Err(err);
+        }
+
+        self.entries[index] = RegionEntry {
+            sector,
+            sector_count,
+            compression: compression as u8
+        };
+        if let Err(err) = self.write_entry(index) {
+            self.entries[index] = old_entry;
+            self.mark_sectors(sector, sector_count, false);
+            return Err(err);
+        }
+        self.mark_sectors(old_entry.sector, old_entry.sector_count, false);
+        Ok(())
+    }
+
+    fn write_sectors(&mut self, sector: u32, sector_count: u16, stored: &[u8]) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(sector as u64 * SECTOR_SIZE))?;
+        self.file.write_all(&(stored.len() as u32).to_le_bytes())?;
+        self.file.write_all(stored)?;
+        // pad the last sector so the file always ends on a sector boundary
+        let padding = sector_count as u64 * SECTOR_SIZE - stored.len() as u64 - 4;
+        self.file.write_all(&vec![0; padding as usize])
+    }
+
+    pub fn remove_chunk(&mut self, chunk_position: ChunkPosition) -> io::Result<()> {
+        let index = Self::entry_index(chunk_position);
+        let entry = self.entries[index];
+        self.mark_sectors(entry.sector, entry.sector_count, false);
+        self.entries[index] = RegionEntry::default();
+        self.write_entry(index)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    fn entry_index(chunk_position: ChunkPosition) -> usize {
+        let x = chunk_position.0.rem_euclid(REGION_SIZE) as usize;
+        let y = chunk_position.1.rem_euclid(REGION_SIZE) as usize;
+        let z = chunk_position.2.rem_euclid(REGION_SIZE) as usize;
+        (x * REGION_SIZE as usize + y) * REGION_SIZE as usize + z
+    }
+
+    fn read_header(&mut self) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut magic = [0; 4];
+        self.file.read_exact(&mut magic)?;
+        if &magic != REGION_MAGIC {
+            return Err(invalid_data("not a region file".to_string()));
+        }
+        let version = read_u16(&mut self.file)?;
+        if version == 0 || version > REGION_VERSION {
+            return Err(invalid_data(format!("unsupported region version {}", version)));
+        }
+        let region_size = read_u8(&mut self.file)?;
+        if region_size as i64 != REGION_SIZE {
+            return Err(invalid_data(format!("region size {} does not match {}", region_size, REGION_SIZE)));
+        }
+
+        let mut table = vec![0; REGION_CHUNKS * ENTRY_SIZE as usize];
+        self.file.seek(SeekFrom::Start(SECTOR_SIZE))?;
+        self.file.read_exact(&mut table)?;
+        for (index, bytes) in table.chunks_exact(ENTRY_SIZE as usize).enumerate() {
+            let entry = RegionEntry {
+                sector: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+                sector_count: u16::from_le_bytes([bytes[4], bytes[5]]),
+                compression: bytes[6]
+            };
+            if !entry.is_empty() && entry.sector < DATA_START_SECTOR {
+                return Err(invalid_data(format!("chunk entry {} points into the header", index)));
+            }
+            self.mark_sectors(entry.sector, entry.sector_count, true);
+            self.entries[index] = entry;
+        }
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let mut header = vec![0; (DATA_START_SECTOR as u64 * SECTOR_SIZE) as usize];
+        header[0..4].copy_from_slice(REGION_MAGIC);
+        header[4..6].copy_from_slice(&REGION_VERSION.to_le_bytes());
+        header[6] = REGION_SIZE as u8;
+
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&header)
+    }
+
+    fn write_entry(&mut self, index: usize) -> io::Result<()> {
+        let entry = self.entries[index];
+        let mut bytes = [0; ENTRY_SIZE as usize];
+        bytes[0..4].copy_from_slice(&entry.sector.to_le_bytes());
+        bytes[4..6].copy_from_slice(&entry.sector_count.to_le_bytes());
+        bytes[6] = entry.compression;
+
+        self.file.seek(SeekFrom::Start(SECTOR_SIZE + index as u64 * ENTRY_SIZE))?;
+        self.file.write_all(&bytes)
+    }
+
+    fn mark_sectors(&mut self, sector: u32, sector_count: u16, used: bool) {
+        let end = sector as usize + sector_count as usize;
+        if self.used_sectors.len() < end {
+            self.used_sectors.resize(end, false);
+        }
+        for sector in sector as usize..end {
+            self.used_sectors[sector] = used;
+        }
+    }
+
+    // first run of free sectors that fits, or the end of the file
+    fn find_free_sectors(&self, sector_count: u16) -> u32 {
+        let mut run_start = DATA_START_SECTOR as usize;
+        let mut run_length = 0;
+        for sector in DATA_START_SECTOR as usize..self.used_sectors.len() {
+            if self.used_sectors[sector] {
+                run_start = sector + 1;
+                run_length = 0;
+            } else {
+                run_length += 1;
+                if run_length == sector_count as usize {
+                    return run_start as u32;
+                }
+            }
+        }
+        run_start as u32
+    }
+}
+
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::world::block_entities::BlockEntityKinds;
+use crate::world::block_updates::SavedUpdate;
+use crate::world::falling_blocks::FallingBlock;
+use crate::world::chunk_map::ChunkPosition;
+use crate::world::metadata::WorldMetadata;
+use crate::world::objects::chunk::Chunk;
+use crate::world::generation::structure_placement::PendingBlocks;
+use crate::world::storage::chunk_format::{decode_chunk, decode_falling_blocks, decode_pending_blocks, decode_scheduled_updates, encode_chunk, encode_falling_blocks, encode_pending_blocks, encode_scheduled_updates};
+use crate::world::storage::region::{Compression, RegionFile, RegionPosition};
+
+// keeps at most this many region files open at once
+const MAX_OPEN_REGIONS: usize = 64;
+
+// a world save on disk, chunks are grouped into region files inside the
+// regions folder of the save directory
+pub struct WorldStorage {
+    directory: PathBuf,
+    regions: HashMap<RegionPosition, RegionFile>,
+    compression: Compression
+}
+impl WorldStorage {
+    pub fn open(directory: &Path) -> io::Result<Self> {
+        fs::create_dir_all(directory.join("regions"))?;
+
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            regions: HashMap::new(),
+            compression: Compression::Deflate
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn load_metadata(&self) -> io::Result<Option<WorldMetadata>> {
+        WorldMetadata::load(&self.directory.join("world.json"))
+    }
+
+    pub fn save_metadata(&self, metadata: &WorldMetadata) -> io::Result<()> {
+        metadata.save(&self.directory.join("world.json"))
+    }
+
+    pub fn load_pending_blocks(&self) -> io::Result<PendingBlocks> {
+        let path = self.directory.join("pending_blocks.dat");
+        if !path.exists() {
+            return Ok(PendingBlocks::new());
+        }
+        decode_pending_blocks(&fs::read(path)?)
+    }
+
+    pub fn save_pending_blocks(&self, pending: &PendingBlocks) -> io::Result<()> {
+        fs::write(self.directory.join("pending_blocks.dat"), encode_pending_blocks(pending))
+    }
+
+    pub fn load_scheduled_updates(&self) -> io::Result<Vec<SavedUpdate>> {
+        let path = self.directory.join("scheduled_updates.dat");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        decode_scheduled_updates(&fs::read(path)?)
+    }
+
+    pub fn save_scheduled_updates(&self, updates: &[SavedUpdate]) -> io::Result<()> {
+        fs::write(self.directory.join("scheduled_updates.dat"), encode_scheduled_updates(updates))
+    }
+
+    pub fn load_falling_blocks(&self) -> io::Result<Vec<FallingBlock>> {
+        let path = self.directory.join("falling_blocks.dat");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        decode_falling_blocks(&fs::read(path)?)
+    }
+
+    pub fn save_falling_blocks(&self, falling_blocks: &[FallingBlock]) -> io::Result<()> {
+        fs::write(self.directory.join("falling_blocks.dat"), encode_falling_blocks(falling_blocks))
+    }
+
+    pub fn load_chunk(&mut self, chunk_position: ChunkPosition, block_entity_kinds: &BlockEntityKinds) -> io::Result<Option<Chunk>> {
+        let region = self.region(chunk_position)?;
+        match region.read_chunk(chunk_position)? {
+            Some(bytes) => Ok(Some(decode_chunk(&bytes, block_entity_kinds)?)),
+            None => Ok(None)
+        }
+    }
+
+    pub fn save_chunk(&mut self, chunk_position: ChunkPosition, chunk: &Chunk) -> io::Result<()> {
+        let bytes = encode_chunk(chunk);
+        let compression = self.compression;
+        self.region(chunk_position)?.write_chunk(chunk_position, &bytes, compression)
+    }
+
+    pub fn contains_chunk(&mut self, chunk_position: ChunkPosition) -> io::Result<bool> {
+        Ok(self.region(chunk_position)?.contains_chunk(chunk_position))
+    }
+
+    // syncs and closes every open region file
+    pub fn flush(&mut self) -> io::Result<()> {
+        for region in self.regions.values_mut() {
+            region.flush()?;
+        }
+        self.regions.clear();
+        Ok(())
+    }
+
+    fn region(&mut self, chunk_position: ChunkPosition) -> io::Result<&mut RegionFile> {
+        let region_position = RegionFile::region_position(chunk_position);
+        if !self.regions.contains_key(&region_position) {
+            if self.regions.len() >= MAX_OPEN_REGIONS {
+                self.flush()?;
+            }
+            let path = self.directory.join("regions").join(RegionFile::file_name(region_position));
+            self.regions.insert(region_position, RegionFile::open(&path)?);
+        }
+        Ok(self.regions.get_mut(&region_position).unwrap())
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::config::{CHUNK_JOBS, CHUNK_LOADS_PER_UPDATE, CHUNK_UNLOAD_MARGIN, VERTICAL_VIEW_DISTANCE};
+use crate::world::chunk_map::{ChunkMap, ChunkPosition};
+use crate::world::generation::generate_chunk::generate_chunk;
+use crate::world::generation::context::GenerationContext;
+use crate::world::generation::structure_placement::GeneratedChunk;
+use crate::world::generation::terrain::{column_position, ColumnHeights};
+use crate::world::objects::chunk::Chunk;
+use crate::world::world::World;
+
+// keeps the chunks around the player loaded. chunks in view are loaded from
+// disk or generated on the rayon pool, nearest first, and chunks that get
+// further away than the view distance plus a margin are saved and dropped,
+// the margin stops chunks on the edge from loading and unloading every step.
+// chunks are cubes, so the world has no top or bottom, the view just follows
+// the player up and down. chunks above the terrain are known to be air from
+// the column heights and are added right away without generating them
+pub struct ChunkStreamer {
+    view_distance: i64,
+    vertical_view_distance: i64,
+    unload_margin: i64,
+
+    player_chunk: Option<ChunkPosition>,
+    queue: VecDeque<ChunkPosition>,
+    generating: HashSet<ChunkPosition>,
+    columns: HashMap<(i64, i64), ColumnHeights>,
+
+    context: Arc<GenerationContext>,
+    sender: Sender<(ChunkPosition, GeneratedChunk)>,
+    receiver: Receiver<(ChunkPosition, GeneratedChunk)>
+}
+impl ChunkStreamer {
+    pub fn new(world: &World, view_distance: i64) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            view_distance,
+            vertical_view_distance: VERTICAL_VIEW_DISTANCE.min(view_distance),
+            unload_margin: CHUNK_UNLOAD_MARGIN,
+
+            player_chunk: None,
+            queue: VecDeque::new(),
+            generating: HashSet::new(),
+            columns: HashMap::new(),
+
+            context: Arc::new(GenerationContext::new(world)),
+            sender,
+            receiver
+        }
+    }
+
+    pub fn set_view_distance(&mut self, view_distance: i64) {
+        self.view_distance = view_distance;
+        self.vertical_view_distance = VERTICAL_VIEW_DISTANCE.min(view_distance);
+        // forces the queue to be rebuilt on the next update
+        self.player_chunk = None;
+    }
+
+    pub fn get_view_distance(&self) -> i64 {
+        self.view_distance
+    }
+
+    // chunks still waiting to be loaded or generated
+    pub fn pending(&self) -> usize {
+        self.queue.len() + self.generating.len()
+    }
+
+    // call once per frame with the player position in block units
+    pub fn update(&mut self, world: &mut World, player_position: (f32, f32, f32)) {
+        let player_block = ChunkMap::block_at_point(player_position.0, player_position.1, player_position.2);
+        let player_chunk = ChunkMap::chunk_position(player_block);
+
+        if self.player_chunk != Some(player_chunk) {
+            self.player_chunk = Some(player_chunk);
+            self.unload_far_chunks(world, player_chunk);
+            self.rebuild_queue(world, player_chunk);
+        }
+
+        self.receive_generated(world, player_chunk);
+        self.start_loading(world);
+    }
+
+    fn in_view(&self, player_chunk: ChunkPosition, position: ChunkPosition, margin: i64) -> bool {
+        let horizontal = self.view_distance + margin;
+        let vertical = self.vertical_view_distance + margin;
+        let dx = position.0 - player_chunk.0;
+        let dy = position.1 - player_chunk.1;
+        let dz = position.2 - player_chunk.2;
+        dx * dx + dz * dz <= horizontal * horizontal && dy.abs() <= vertical
+    }
+
+    fn unload_far_chunks(&mut self, world: &mut World, player_chunk: ChunkPosition) {
+        let far_chunks: Vec<ChunkPosition> = world.get_chunks().positions()
+            .filter(|position| !self.in_view(player_chunk, *position, self.unload_margin))
+            .collect();
+
+        for position in far_chunks {
+            if let Err(err) = world.unload_chunk(position) {
+                eprintln!("Failed to save chunk {:?}: {:?}", position, err);
+            }
+        }
+    }
+
+    fn rebuild_queue(&mut self, world: &World, player_chunk: ChunkPosition) {
+        let horizontal = self.view_distance;
+        let vertical = self.vertical_view_distance;
+
+        let mut positions: Vec<(i64, ChunkPosition)> = Vec::new();
+        for x in -horizontal..=horizontal {
+            for y in -vertical..=vertical {
+                for z in -horizontal..=horizontal {
+                    let position = (player_chunk.0 + x, player_chunk.1 + y, player_chunk.2 + z);
+                    if !self.in_view(player_chunk, position, 0) {
+                        continue;
+                    }
+                    if world.get_chunks().contains_chunk(position) || self.generating.contains(&position) {
+                        continue;
+                    }
+                    positions.push((x * x + y * y + z * z, position));
+                }
+            }
+        }
+        positions.sort_by_key(|(distance, _)| *distance);
+
+        // the column heights of columns that left the view aren't needed anymore
+        let margin = self.unload_margin;
+        self.columns.retain(|column, _| {
+            let dx = column.0 - player_chunk.0;
+            let dz = column.1 - player_chunk.2;
+            dx * dx + dz * dz <= (horizontal + margin) * (horizontal + margin)
+        });
+
+        self.queue = positions.into_iter().map(|(_, position)| position).collect();
+    }
+
+    fn receive_generated(&mut self, world: &mut World, player_chunk: ChunkPosition) {
+        while let Ok((position, generated)) = self.receiver.try_recv() {
+            self.generating.remove(&position);
+            // the player may have walked away while this chunk was generating
+            if !self.in_view(player_chunk, position, self.unload_margin) || world.get_chunks().contains_chunk(position) {
+                continue;
+            }
+            world.insert_generated_chunk(position, generated);
+        }
+    }
+
+    fn start_loading(&mut self, world: &mut World) {
+        let mut loads = 0;
+        while loads < CHUNK_LOADS_PER_UPDATE && self.generating.len() < CHUNK_JOBS {
+            let Some(position) = self.queue.pop_front() else {
+                break;
+            };
+            if world.get_chunks().contains_chunk(position) || self.generating.contains(&position) {
+                continue;
+            }
+
+            // saved chunks come straight from disk, everything else is generated
+            loads += 1;
+            match world.load_chunk(position) {
+                Ok(true) => continue,
+                Ok(false) => {}
+                // generating it would overwrite whatever was saved in it, so the chunk
+                // stays unloaded and is queued again the next time the player changes chunk
+                Err(err) => {
+                    eprintln!("Failed to load chunk {:?}, leaving it unloaded: {:?}", position, err);
+                    continue;
+                }
+            }
+
+            let context = &self.context;
+            let column = self.columns.entry(column_position(position))
+                .or_insert_with(|| ColumnHeights::generate(position.0, position.2, &context.randomness_functions, &context.terrain));
+            if self.context.oceans.is_air_chunk(column, position.1) {
+                world.insert_generated_chunk(position, GeneratedChunk::new(Chunk::new()));
+                continue;
+            }
+
+            self.generating.insert(position);
+            let sender = self.sender.clone();
+            let context = Arc::clone(&self.context);
+            rayon::spawn(move || {
+                let generated = generate_chunk(position, &context);
+                // the streamer may already be gone when the game is closing
+                let _ = sender.send((position, generated));
+            });
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::world::blocks::BlockRegistry;
+use crate::world::chunk_map::BlockPosition;
+use crate::world::objects::chunk::Block;
+use crate::world::world::World;
+use crate::world::world_edit::{Clipboard, ClipboardBlock, Region};
+
+// one block of a structure file, the block is written like parse_block reads it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StructureBlockDef {
+    pub position: [i64; 3],
+    pub block: String
+}
+
+// a structure as it is described in assets/structures
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StructureDef {
+    pub structure_name: String,
+    pub blocks: Vec<StructureBlockDef>
+}
+impl StructureDef {
+    // one block per line like the hand written files, so exports stay readable in diffs
+    pub fn to_json(&self) -> String {
+        let blocks: Vec<String> = self.blocks.iter()
+            .map(|block| format!("        {}", serde_json::to_string(block).unwrap_or_default()))
+            .collect();
+        format!(
+            "{{\n    \"structure_name\": {},\n    \"blocks\": [\n{}\n    ]\n}}\n",
+            serde_json::to_string(&self.structure_name).unwrap_or_default(),
+            blocks.join(",\n")
+        )
+    }
+}
+
+// a structure with its blocks looked up, positions are relative to its origin
+#[derive(Clone, Debug)]
+pub struct Structure {
+    pub name: String,
+    pub blocks: Vec<(BlockPosition, Block)>
+}
+impl Structure {
+    // fails with the name of the first block that isn't loaded
+    pub fn from_def(structure: &StructureDef, blocks: &BlockRegistry) -> Result<Self, String> {
+        let mut structure_blocks = Vec::with_capacity(structure.blocks.len());
+        for block in &structure.blocks {
+            let Some(parsed) = blocks.parse_block(&block.block) else {
+                return Err(format!("unknown block {} at {:?}", block.block, block.position));
+            };
+            structure_blocks.push(((block.position[0], block.position[1], block.position[2]), parsed));
+        }
+        Ok(Self {
+            name: structure.structure_name.clone(),
+            blocks: structure_blocks
+        })
+    }
+
+    pub fn to_def(&self, blocks: &BlockRegistry, include_states: bool) -> StructureDef {
+        StructureDef {
+            structure_name: self.name.clone(),
+            blocks: self.blocks.iter()
+                .map(|(position, block)| StructureBlockDef {
+                    position: [position.0, position.1, position.2],
+                    block: short_block_name(blocks, *block, include_states)
+                })
+                .collect()
+        }
+    }
+
+    // so structures can be pasted with the world edit tools
+    pub fn to_clipboard(&self) -> Clipboard {
+        Clipboard {
+            blocks: self.blocks.iter()
+                .map(|(offset, block)| ClipboardBlock { offset: *offset, block: *block, block_entity: None })
+                .collect()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ExportOptions {
+    pub include_air: bool,
+    pub include_states: bool,
+    // the world position that becomes 0, 0, 0 in the file, the lowest corner when not set
+    pub origin: Option<BlockPosition>
+}
+
+// reads a region of the world into a structure, blocks in unloaded chunks are left out
+pub fn export_structure(world: &World, name: &str, region: Region, options: &ExportOptions) -> Structure {
+    let origin = options.origin.unwrap_or(region.min);
+    let blocks = region.positions()
+        .filter_map(|position| {
+            let block = world.get_block(position)?;
+            if block.is_air() && !options.include_air {
+                return None;
+            }
+            let block = if options.include_states { block } else { Block::from_id(block.id) };
+            Some(((position.0 - origin.0, position.1 - origin.1, position.2 - origin.2), block))
+        })
+        .collect();
+    Structure {
+        name: name.to_string(),
+        blocks
+    }
+}
+
+// every loaded structure by name
+#[derive(Clone, Default)]
+pub struct Structures {
+    structures: HashMap<String, Structure>
+}
+impl Structures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, structure: Structure) {
+        self.structures.insert(structure.name.clone(), structure);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Structure> {
+        self.structures.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.structures.keys().map(|name| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.structures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.structures.is_empty()
+    }
+}
+
+// the bare name when no other block shares it, like the hand written files use
+fn short_block_name(blocks: &BlockRegistry, block: Block, include_states: bool) -> String {
+    let block = if include_states { block } else { Block::from_id(block.id) };
+    let full_name = blocks.format_block(block);
+    match blocks.get(block.id) {
+        Some(definition) if blocks.get_id(&definition.name) == Some(block.id) => {
+            full_name.trim_start_matches(&format!("{}:", definition.creator)).to_string()
+        }
+        _ => full_name
+    }
+}
@@ -0,0 +1,162 @@
+use std::f32::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::DAY_LENGTH;
+
+// times of day as a fraction of the day, the day starts at sunrise
+pub const SUNRISE: f32 = 0.0;
+pub const MORNING: f32 = 1.0 / 24.0;
+pub const NOON: f32 = 0.25;
+pub const SUNSET: f32 = 0.5;
+pub const MIDNIGHT: f32 = 0.75;
+
+// the sky never gets fully dark, and faces turned away from the sun still get some of its light
+const NIGHT_SKY_BRIGHTNESS: f32 = 0.2;
+const DAY_AMBIENT: f32 = 0.6;
+const NIGHT_AMBIENT: f32 = 0.8;
+
+const DAY_SKY_COLOR: [f32; 3] = [0.2, 0.247, 0.314];
+const NIGHT_SKY_COLOR: [f32; 3] = [0.01, 0.012, 0.03];
+const DAWN_SKY_COLOR: [f32; 3] = [0.55, 0.3, 0.2];
+
+// the world clock, saved with the world. it counts ticks since the world
+// was created and only moves while it isn't frozen
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct WorldTime {
+    pub ticks: u64,
+    // ticks in one day and night
+    pub day_length: u64,
+    pub frozen: bool
+}
+impl WorldTime {
+    pub fn new() -> Self {
+        Self {
+            // new worlds start in the morning
+            ticks: (MORNING * DAY_LENGTH as f32) as u64,
+            day_length: DAY_LENGTH,
+            frozen: false
+        }
+    }
+
+    pub fn advance(&mut self) {
+        if !self.frozen {
+            self.ticks = self.ticks.saturating_add(1);
+        }
+    }
+
+    // how many whole days have passed
+    pub fn day(&self) -> u64 {
+        self.ticks / self.day_length.max(1)
+    }
+
+    // from 0 at sunrise to 1 at the next one
+    pub fn time_of_day(&self) -> f32 {
+        let day_length = self.day_length.max(1);
+        (self.ticks % day_length) as f32 / day_length as f32
+    }
+
+    // moves the clock forward to the next time the day is at the fraction, so days never go backwards
+    pub fn set_time_of_day(&mut self, fraction: f32) {
+        let day_length = self.day_length.max(1);
+        let target = (fraction.rem_euclid(1.0) * day_length as f32) as u64;
+        let current = self.ticks % day_length;
+        self.ticks = self.ticks - current + target + if target < current { day_length } else { 0 };
+    }
+
+    // points from the world towards the sun, it rises in the east (+x) and sets in the west
+    pub fn sun_direction(&self) -> [f32; 3] {
+        let angle = self.time_of_day() * TAU;
+        normalize([angle.cos(), angle.sin(), 0.3])
+    }
+
+    pub fn moon_direction(&self) -> [f32; 3] {
+        self.sun_direction().map(|axis| -axis)
+    }
+
+    // the sun by day and the moon by night
+    pub fn light_direction(&self) -> [f32; 3] {
+        if self.daylight() > 0.0 { self.sun_direction() } else { self.moon_direction() }
+    }
+
+    // 0 at night and 1 during the day, with a short dawn and dusk in between
+    pub fn daylight(&self) -> f32 {
+        let height = (self.time_of_day() * TAU).sin();
+        (height * 2.0 + 0.5).clamp(0.0, 1.0)
+    }
+
+    // how strong sky light is compared to noon
+    pub fn sky_brightness(&self) -> f32 {
+        lerp(NIGHT_SKY_BRIGHTNESS, 1.0, self.daylight())
+    }
+
+    // what a sky light level is worth right now, for anything that cares
+    // how dark it is like spawning or plants
+    pub fn effective_sky_light(&self, level: u8) -> u8 {
+        (level as f32 * self.sky_brightness()).round() as u8
+    }
+
+    pub fn ambient_light(&self) -> f32 {
+        lerp(NIGHT_AMBIENT, DAY_AMBIENT, self.daylight())
+    }
+
+    // the clear and fog colour, reddish while the sun is close to the horizon
+    pub fn sky_color(&self) -> [f32; 3] {
+        let daylight = self.daylight();
+        let dawn = (1.0 - (daylight * 2.0 - 1.0).abs()) * 0.5;
+        [0, 1, 2].map(|channel| {
+            let color = lerp(NIGHT_SKY_COLOR[channel], DAY_SKY_COLOR[channel], daylight);
+            lerp(color, DAWN_SKY_COLOR[channel], dawn)
+        })
+    }
+
+    // everything the renderer needs to draw the sky and light the world
+    pub fn sky(&self) -> Sky {
+        Sky {
+            light_direction: self.light_direction(),
+            sky_color: self.sky_color(),
+            sky_brightness: self.sky_brightness(),
+            ambient_light: self.ambient_light(),
+            fog_distance: 1.0
+        }
+    }
+}
+impl Default for WorldTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// what the clock looks like at one moment
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sky {
+    pub light_direction: [f32; 3],
+    pub sky_color: [f32; 3],
+    pub sky_brightness: f32,
+    pub ambient_light: f32,
+    // how far the fog starts compared to a clear day
+    pub fog_distance: f32
+}
+
+// noon, midnight and so on, or a number of ticks into the day
+pub fn parse_time_of_day(text: &str, day_length: u64) -> Option<f32> {
+    match text {
+        "sunrise" => Some(SUNRISE),
+        "day" | "morning" => Some(MORNING),
+        "noon" => Some(NOON),
+        "sunset" => Some(SUNSET),
+        "night" | "midnight" => Some(MIDNIGHT),
+        _ => text.parse::<u64>().ok().map(|ticks| (ticks % day_length.max(1)) as f32 / day_length.max(1) as f32)
+    }
+}
+
+fn lerp(from: f32, to: f32, amount: f32) -> f32 {
+    from + (to - from) * amount
+}
+
+fn normalize(vector: [f32; 3]) -> [f32; 3] {
+    let length = (vector[0] * vector[0] + vector[1] * vector[1] + vector[2] * vector[2]).sqrt();
+    vector.map(|axis| axis / length)
+}
+
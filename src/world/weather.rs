@@ -0,0 +1,298 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{CHUNK_SIZE_X, CHUNK_SIZE_Z, SNOW_TICKS_PER_COLUMN, WEATHER_CELL_SIZE, WEATHER_LENGTH, WEATHER_TRANSITION};
+use crate::world::blocks::LAYERS_STATE;
+use crate::world::chunk_map::{BlockPosition, ChunkMap};
+use crate::world::generation::climate::{climate_at, Climate};
+use crate::world::generation::randomness::RandomnessFunctions;
+use crate::world::heightmaps::HeightmapKind;
+use crate::world::objects::chunk::Block;
+use crate::world::time::Sky;
+use crate::world::world::World;
+
+// snow settles as layers of this block, a full stack becomes a snow block
+pub const SNOW_LAYER_BLOCK: &str = "snow_layer";
+pub const SNOW_BLOCK: &str = "snow";
+
+// chance of rain at full moisture, and of a storm when it rains in the heat
+const MAX_RAIN_CHANCE: f32 = 0.6;
+const MAX_THUNDER_CHANCE: f32 = 0.5;
+// it rains or snows once the clouds are this thick
+const PRECIPITATION_THRESHOLD: f32 = 0.3;
+// deserts stay dry under the darkest clouds
+const DRY_MOISTURE: f32 = 15.0;
+const FREEZING: f32 = 0.0;
+// lightning flashes for this many ticks, with this chance in a storm
+const LIGHTNING_TICKS: u64 = 4;
+const LIGHTNING_CHANCE: f32 = 0.01;
+const LIGHTNING_SKY_COLOR: [f32; 3] = [0.7, 0.7, 0.8];
+// keeps the weather rolls apart from chunk rngs at the same position
+const WEATHER_SALT: u64 = 0x5745415448455221;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Thunder
+}
+impl WeatherKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "clear" => Some(WeatherKind::Clear),
+            "rain" => Some(WeatherKind::Rain),
+            "thunder" => Some(WeatherKind::Thunder),
+            _ => None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "clear",
+            WeatherKind::Rain => "rain",
+            WeatherKind::Thunder => "thunder"
+        }
+    }
+
+    // how much rain and how much storm it brings
+    fn strength(self) -> (f32, f32) {
+        match self {
+            WeatherKind::Clear => (0.0, 0.0),
+            WeatherKind::Rain => (1.0, 0.0),
+            WeatherKind::Thunder => (1.0, 1.0)
+        }
+    }
+}
+
+// what falls from the sky at one place, rain turns into snow where it is freezing
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Precipitation {
+    None,
+    Rain,
+    Snow
+}
+
+// weather set with the weather command, it fades in from the tick it was set
+// and back out to the natural weather before it ends
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct ForcedWeather {
+    pub kind: WeatherKind,
+    pub from: u64,
+    pub until: u64
+}
+
+// saved with the world, the natural weather only depends on the seed and the clock
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+pub struct WeatherSettings {
+    pub forced: Option<ForcedWeather>
+}
+
+// the weather at one place and moment
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LocalWeather {
+    // how overcast it is from 0 to 1, and how much of that is a storm
+    pub rain: f32,
+    pub thunder: f32,
+    pub precipitation: Precipitation,
+    pub lightning: bool,
+    pub climate: Climate
+}
+impl LocalWeather {
+    pub fn kind(&self) -> WeatherKind {
+        if self.thunder >= 0.5 {
+            WeatherKind::Thunder
+        } else if self.rain >= 0.5 {
+            WeatherKind::Rain
+        } else {
+            WeatherKind::Clear
+        }
+    }
+}
+
+// what the weather does to the world, mods can swap these out on the world
+#[derive(Clone, Copy)]
+pub struct WeatherHooks {
+    // snow settling on top of the block at the position
+    pub snow: fn(&mut World, BlockPosition),
+    // the sky and fog under the weather, before they are drawn
+    pub sky: fn(&LocalWeather, Sky) -> Sky
+}
+impl WeatherHooks {
+    pub fn new() -> Self {
+        Self {
+            snow: layer_snow,
+            sky: darken_sky
+        }
+    }
+}
+impl Default for WeatherHooks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// how likely a climate is to bring rain, and a thunderstorm, in every weather length
+pub fn weather_chances(climate: Climate) -> (f32, f32) {
+    let rain = (climate.moisture / 100.0).clamp(0.0, 1.0) * MAX_RAIN_CHANCE;
+    // storms need warm and wet air
+    let thunder = rain * ((climate.temperature - 10.0) / 20.0).clamp(0.0, 1.0) * MAX_THUNDER_CHANCE;
+    (rain, thunder)
+}
+
+// the weather at a position when ticks is the world clock
+pub fn weather_at(randomness_functions: &RandomnessFunctions, settings: &WeatherSettings, ticks: u64, position: BlockPosition) -> LocalWeather {
+    let climate = climate_at(position.0, position.2, randomness_functions);
+    let (mut rain, mut thunder) = natural_strength(randomness_functions, position.0, position.2, ticks);
+    if let Some(forced) = settings.forced.filter(|forced| ticks < forced.until) {
+        let (forced_rain, forced_thunder) = forced.kind.strength();
+        let fade_in = ticks.saturating_sub(forced.from) as f32 / WEATHER_TRANSITION as f32;
+        let fade_out = (forced.until - ticks) as f32 / WEATHER_TRANSITION as f32;
+        let amount = fade_in.min(fade_out).clamp(0.0, 1.0);
+        rain = lerp(rain, forced_rain, amount);
+        thunder = lerp(thunder, forced_thunder, amount);
+    }
+
+    let precipitation = if rain < PRECIPITATION_THRESHOLD || climate.moisture < DRY_MOISTURE {
+        Precipitation::None
+    } else if climate.temperature_at(position.1) < FREEZING {
+        Precipitation::Snow
+    } else {
+        Precipitation::Rain
+    };
+    let (cell_x, cell_z) = (position.0.div_euclid(WEATHER_CELL_SIZE), position.2.div_euclid(WEATHER_CELL_SIZE));
+    let flash = roll(randomness_functions, cell_x, (ticks / LIGHTNING_TICKS) as i64, cell_z);
+    LocalWeather {
+        rain,
+        thunder,
+        precipitation,
+        lightning: thunder >= 0.5 && flash < LIGHTNING_CHANCE,
+        climate
+    }
+}
+
+// every cell picks its weather for every weather length from its climate,
+// the cells around a column are blended so nothing changes at a line and the
+// new weather takes the transition to set in
+fn natural_strength(randomness_functions: &RandomnessFunctions, x: i64, z: i64, ticks: u64) -> (f32, f32) {
+    let period = ticks / WEATHER_LENGTH;
+    let change = ((ticks % WEATHER_LENGTH) as f32 / WEATHER_TRANSITION as f32).min(1.0);
+
+    // between the centers of the four closest cells
+    let cell_x = x as f32 / WEATHER_CELL_SIZE as f32 - 0.5;
+    let cell_z = z as f32 / WEATHER_CELL_SIZE as f32 - 0.5;
+    let (first_x, first_z) = (cell_x.floor(), cell_z.floor());
+    let (blend_x, blend_z) = (cell_x - first_x, cell_z - first_z);
+
+    let (mut rain, mut thunder) = (0.0, 0.0);
+    for (offset_x, weight_x) in [(0, 1.0 - blend_x), (1, blend_x)] {
+        for (offset_z, weight_z) in [(0, 1.0 - blend_z), (1, blend_z)] {
+            let cell = (first_x as i64 + offset_x, first_z as i64 + offset_z);
+            let now = cell_weather(randomness_functions, cell, period).strength();
+            let before = match period {
+                0 => now,
+                _ => cell_weather(randomness_functions, cell, period - 1).strength()
+            };
+            let weight = weight_x * weight_z;
+            rain += lerp(before.0, now.0, change) * weight;
+            thunder += lerp(before.1, now.1, change) * weight;
+        }
+    }
+    (rain, thunder)
+}
+
+fn cell_weather(randomness_functions: &RandomnessFunctions, cell: (i64, i64), period: u64) -> WeatherKind {
+    let center_x = cell.0 * WEATHER_CELL_SIZE + WEATHER_CELL_SIZE / 2;
+    let center_z = cell.1 * WEATHER_CELL_SIZE + WEATHER_CELL_SIZE / 2;
+    let (rain, thunder) = weather_chances(climate_at(center_x, center_z, randomness_functions));
+    let roll = roll(randomness_functions, cell.0, period as i64, cell.1);
+    if roll < thunder {
+        WeatherKind::Thunder
+    } else if roll < rain {
+        WeatherKind::Rain
+    } else {
+        WeatherKind::Clear
+    }
+}
+
+// from 0 to 1, the same for the same seed and inputs
+fn roll(randomness_functions: &RandomnessFunctions, x: i64, y: i64, z: i64) -> f32 {
+    let hash = randomness_functions.position_hash(x, y, z) ^ WEATHER_SALT;
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+// clouds darken the sky and pull the fog in, snow more than rain, and
+// lightning lights everything up for a moment
+pub fn darken_sky(weather: &LocalWeather, sky: Sky) -> Sky {
+    if weather.lightning {
+        return Sky {
+            sky_color: LIGHTNING_SKY_COLOR,
+            sky_brightness: 1.0,
+            ..sky
+        };
+    }
+    let darkness = weather.rain * 0.35 + weather.thunder * 0.35;
+    let gray = (sky.sky_color[0] + sky.sky_color[1] + sky.sky_color[2]) / 3.0;
+    let snow_fog = if weather.precipitation == Precipitation::Snow { 0.2 } else { 0.0 };
+    Sky {
+        sky_color: sky.sky_color.map(|channel| lerp(channel, gray, weather.rain * 0.7) * (1.0 - darkness * 0.5)),
+        sky_brightness: sky.sky_brightness * (1.0 - darkness),
+        fog_distance: sky.fog_distance * (1.0 - weather.rain * (0.35 + snow_fog) - weather.thunder * 0.15),
+        ..sky
+    }
+}
+
+// snow settles on a random column in every loaded column of chunks where it is snowing
+pub fn tick(world: &mut World) {
+    let columns: Vec<(i64, i64)> = world.get_chunks().columns().collect();
+    for (chunk_x, chunk_z) in columns {
+        for _ in 0..SNOW_TICKS_PER_COLUMN {
+            let rng = world.get_rng_mut();
+            let local = (rng.gen_range(0..CHUNK_SIZE_X as usize), 0, rng.gen_range(0..CHUNK_SIZE_Z as usize));
+            let (x, _, z) = ChunkMap::world_position((chunk_x, 0, chunk_z), local);
+            let Some(top) = world.highest_block(x, z, HeightmapKind::MotionBlocking) else {
+                continue;
+            };
+            if world.precipitation_at((x, top + 1, z)) == Precipitation::Snow {
+                let snow = world.get_weather_hooks().snow;
+                snow(world, (x, top, z));
+            }
+        }
+    }
+}
+
+// snow lands on full blocks and piles up layer by layer on top of itself,
+// a full stack of layers becomes a snow block. layers collide, so the
+// position is the layer itself once snow has started to settle there
+pub fn layer_snow(world: &mut World, position: BlockPosition) {
+    let above = (position.0, position.1 + 1, position.2);
+    let (Some(block), Some(above_block)) = (world.get_block(position), world.get_block(above)) else {
+        return;
+    };
+    let blocks = world.get_blocks();
+    let (Some(layer_id), Some(snow)) = (blocks.get_id(SNOW_LAYER_BLOCK), blocks.parse_block(SNOW_BLOCK)) else {
+        return;
+    };
+    let Some(layer) = blocks.get(layer_id) else {
+        return;
+    };
+
+    let (position, block) = if above_block.id == layer_id { (above, above_block) } else { (position, block) };
+    if block.id == layer_id {
+        let layers = blocks.layers(block);
+        let next = layer.with_state_value(block.state, LAYERS_STATE, &(layers + 1).to_string());
+        let block = match next {
+            Some(state) => Block::new(layer_id, state),
+            None => snow
+        };
+        world.set_block(position, block);
+    } else if above_block.is_air() && blocks.occludes(block) {
+        let state = layer.with_state_value(0, LAYERS_STATE, "1").unwrap_or(0);
+        world.set_block(above, Block::new(layer_id, state));
+    }
+}
+
+fn lerp(from: f32, to: f32, amount: f32) -> f32 {
+    from + (to - from) * amount
+}
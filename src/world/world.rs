@@ -1,27 +1,665 @@
-use crate::world::assets::load_biomes;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use crate::world::assets::{load_biomes, load_blocks, load_shapes, load_structures};
 use crate::world::{object::Object};
-use crate::world::generation::randomness;
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition};
+use crate::world::objects::chunk::{Block, BlockId, Chunk};
+use crate::world::heightmaps::{HeightmapKind, Heightmaps};
+use crate::world::light;
+use crate::world::fluids::{self, FluidTicks};
+use crate::world::random_ticks::{self, RandomTickHandlers};
+use crate::world::block_updates::{self, BlockUpdateHandlers, ScheduledUpdates, UpdateReason};
+use crate::world::falling_blocks::{self, FallingBlock};
+use crate::world::chunk_map::NEIGHBOUR_OFFSETS;
+use crate::world::storage::world_storage::WorldStorage;
+use crate::world::metadata::WorldMetadata;
+use crate::world::time::{Sky, WorldTime};
+use crate::world::weather::{self, LocalWeather, Precipitation, WeatherHooks, WeatherSettings};
+use crate::world::generation::randomness::{self, RandomnessFunctions};
 use crate::world::biomes;
+use crate::world::blocks::BlockRegistry;
+use crate::world::block_states::Placement;
+use crate::world::block_entities::{BlockEntity, BlockEntityKinds};
+use crate::world::edit_journal::{BlockChange, EditJournal, PlayerId};
+use crate::world::world_edit::EditSession;
+use crate::world::structures::Structures;
+use crate::world::generation::structure_placement::{apply_pending, GeneratedChunk, PendingBlocks};
+use crate::world::generation::terrain::{terrain_height, Terrain};
+use crate::config::CHUNK_SIZE_Y;
+use crate::world::objects::chunk::AIR;
+use crate::config::{BLOCK_UPDATES_PER_TICK, EDIT_HISTORY_LENGTH, FLUID_UPDATES_PER_TICK, RANDOM_TICKS_PER_CHUNK};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 pub struct World {
+    metadata: WorldMetadata,
     randomness_functions: randomness::RandomnessFunctions,
 
     biomes: biomes::Biomes,
+    // the shape of the terrain, for knowing where the ground is without generating it
+    terrain: Terrain,
+    blocks: BlockRegistry,
+    block_entity_kinds: BlockEntityKinds,
+    random_tick_handlers: RandomTickHandlers,
+    block_update_handlers: BlockUpdateHandlers,
+    weather_hooks: WeatherHooks,
+    structures: Structures,
+
+    chunks: ChunkMap,
+    // structure blocks for chunks that haven't been generated or loaded yet
+    pending_blocks: PendingBlocks,
+    // none for worlds that only live in memory
+    storage: Option<WorldStorage>,
+    // undo and redo of everything players build
+    journal: EditJournal,
+    // selections and clipboards of the world edit tools
+    edit_sessions: HashMap<PlayerId, EditSession>,
+    // ticks since the world was opened
+    tick: u64,
+    fluid_ticks: FluidTicks,
+    scheduled_updates: ScheduledUpdates,
+    // blocks like sand while they fall, they are back in their chunk once they land
+    falling_blocks: Vec<FallingBlock>,
+    // for the simulation, generation has its own so it never depends on what happened in the world
+    rng: ChaCha8Rng,
 
     objects: Vec<Object>
 }
 impl World {
-    pub fn new() -> Self{
-        let biomes: biomes::Biomes = biomes::Biomes::new();
-        load_biomes(&biomes);
+    // an in memory world with a random seed
+    pub fn new() -> Self {
+        Self::from_randomness(randomness::RandomnessFunctions::new(), &HashMap::new())
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
+        Self::from_randomness(randomness::RandomnessFunctions::from_seed(seed), &HashMap::new())
+    }
+
+    // the block ids are those of a save, new worlds pass an empty map and
+    // keep the order the blocks were loaded in
+    fn from_randomness(randomness_functions: RandomnessFunctions, block_ids: &HashMap<String, BlockId>) -> Self {
+        let mut blocks = BlockRegistry::new();
+        load_shapes(&mut blocks);
+        load_blocks(&mut blocks);
+        // structures and the generator look their blocks up once, so the ids have to be final first
+        blocks.assign_ids(block_ids);
+
+        let mut structures = Structures::new();
+        load_structures(&mut structures, &blocks);
+
+        let mut biomes = biomes::Biomes::new();
+        load_biomes(&mut biomes, &blocks, &structures);
+
+        let terrain = Terrain::new(&biomes);
+        let rng = ChaCha8Rng::seed_from_u64(randomness_functions.seed);
 
         Self {
-            randomness_functions: randomness::RandomnessFunctions::new(),
-            biomes: biomes,
+            metadata: WorldMetadata::new("world", randomness_functions.seed),
+            randomness_functions,
+            biomes,
+            terrain,
+            blocks,
+            block_entity_kinds: BlockEntityKinds::new(),
+            random_tick_handlers: RandomTickHandlers::new(),
+            block_update_handlers: BlockUpdateHandlers::new(),
+            weather_hooks: WeatherHooks::new(),
+            structures,
+            chunks: ChunkMap::new(),
+            pending_blocks: PendingBlocks::new(),
+            storage: None,
+            journal: EditJournal::new(EDIT_HISTORY_LENGTH),
+            edit_sessions: HashMap::new(),
+            tick: 0,
+            fluid_ticks: FluidTicks::new(),
+            scheduled_updates: ScheduledUpdates::new(),
+            falling_blocks: Vec::new(),
+            rng,
             objects: Vec::new()
         }
     }
 
+    // opens the save in the given directory, creating it when it doesn't exist.
+    // the seed is only used for new worlds, a random one is picked without it
+    pub fn open(directory: &Path, seed: Option<&str>) -> io::Result<Self> {
+        let storage = WorldStorage::open(directory)?;
+        let metadata = match storage.load_metadata()? {
+            Some(metadata) => metadata,
+            None => {
+                let randomness_functions = match seed {
+                    Some(seed) => RandomnessFunctions::from_string_seed(seed),
+                    None => RandomnessFunctions::new()
+                };
+                let name = directory.file_name().and_then(|name| name.to_str()).unwrap_or("world");
+                WorldMetadata::new(name, randomness_functions.seed)
+            }
+        };
+
+        // saved chunks store ids, so the blocks have to keep the ids they were saved with
+        let mut world = Self::from_randomness(RandomnessFunctions::from_seed(metadata.seed), &metadata.block_ids);
+        world.metadata = metadata;
+        world.metadata.block_ids = world.blocks.id_map();
+        storage.save_metadata(&world.metadata)?;
+        world.pending_blocks = storage.load_pending_blocks()?;
+        world.scheduled_updates = ScheduledUpdates::from_saved(storage.load_scheduled_updates()?);
+        world.falling_blocks = storage.load_falling_blocks()?;
+        world.storage = Some(storage);
+        Ok(world)
+    }
+
+    pub fn get_metadata(&self) -> &WorldMetadata {
+        &self.metadata
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.randomness_functions.seed
+    }
+
+    pub fn get_randomness_functions(&self) -> &RandomnessFunctions {
+        &self.randomness_functions
+    }
+
+    // loads a chunk from disk into the world, returns false when it was never saved
+    pub fn load_chunk(&mut self, position: ChunkPosition) -> io::Result<bool> {
+        let Some(storage) = self.storage.as_mut() else {
+            return Ok(false);
+        };
+        match storage.load_chunk(position, &self.block_entity_kinds)? {
+            Some(mut chunk) => {
+                // structures of chunks generated while this one was on disk
+                let changed = self.pending_blocks.take(position).is_some_and(|blocks| apply_pending(&mut chunk, &blocks));
+                // chunks saved before heightmaps existed get them now
+                if changed || chunk.heightmaps().is_none() {
+                    self.compute_heightmaps(&mut chunk);
+                }
+                self.chunks.insert_chunk(position, chunk);
+                self.light_chunk(position);
+                self.scheduled_updates.load_chunk(position, self.tick);
+                if !changed {
+                    self.chunks.mark_saved(position);
+                }
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+    // adds a chunk from the generator, its pending blocks go straight into
+    // neighbours that are loaded and wait for the others
+    pub fn insert_generated_chunk(&mut self, position: ChunkPosition, generated: GeneratedChunk) {
+        let mut chunk = generated.chunk;
+        if let Some(blocks) = self.pending_blocks.take(position) {
+            apply_pending(&mut chunk, &blocks);
+        }
+        self.compute_heightmaps(&mut chunk);
+        self.chunks.insert_chunk(position, chunk);
+        self.light_chunk(position);
+        self.scheduled_updates.load_chunk(position, self.tick);
+
+        for (neighbour_position, blocks) in generated.pending.into_chunks() {
+            if neighbour_position == position {
+                continue;
+            }
+            let Some(neighbour) = self.chunks.get_chunk_mut(neighbour_position) else {
+                self.pending_blocks.add_to_chunk(neighbour_position, blocks);
+                continue;
+            };
+            if !apply_pending(neighbour, &blocks) {
+                continue;
+            }
+            neighbour.set_heightmaps(Some(Heightmaps::compute(neighbour, &self.blocks)));
+            self.chunks.mark_changed(neighbour_position);
+            for (local_position, _) in blocks {
+                light::update_light(&mut self.chunks, &self.blocks, ChunkMap::world_position(neighbour_position, local_position));
+            }
+        }
+    }
+
+    // columns without a loaded chunk above see the sky when that chunk is above the terrain
+    fn light_chunk(&mut self, position: ChunkPosition) {
+        let randomness_functions = &self.randomness_functions;
+        let terrain = &self.terrain;
+        let above_bottom = (position.1 + 1) * CHUNK_SIZE_Y as i64;
+        light::light_chunk(&mut self.chunks, &self.blocks, position, |x, z| {
+            let column = ChunkMap::world_position(position, (x, 0, z));
+            above_bottom > terrain_height(column.0, column.2, randomness_functions, terrain)
+        });
+    }
+
+    fn compute_heightmaps(&self, chunk: &mut Chunk) {
+        chunk.set_heightmaps(Some(Heightmaps::compute(chunk, &self.blocks)));
+    }
+
+    // writes the chunk to disk if it changed and removes it from the world
+    pub fn unload_chunk(&mut self, position: ChunkPosition) -> io::Result<()> {
+        self.unload_block_entities(position);
+        falling_blocks::land_in_chunk(self, position);
+        self.scheduled_updates.unload_chunk(position, self.tick);
+        self.save_chunk(position)?;
+        self.chunks.remove_chunk(position);
+        Ok(())
+    }
+
+    // runs the unload hooks, every block entity is put back afterwards so
+    // whatever the hook changed is saved with the chunk
+    fn unload_block_entities(&mut self, position: ChunkPosition) {
+        let Some(chunk) = self.chunks.get_chunk(position) else {
+            return;
+        };
+        for local_position in chunk.block_entity_positions() {
+            let block_position = ChunkMap::world_position(position, local_position);
+            if let Some(mut block_entity) = self.chunks.remove_block_entity(block_position) {
+                block_entity.on_unload(self, block_position);
+                let _ = self.chunks.set_block_entity(block_position, block_entity);
+            }
+        }
+    }
+
+    pub fn save_chunk(&mut self, position: ChunkPosition) -> io::Result<()> {
+        if !self.chunks.is_unsaved(position) {
+            return Ok(());
+        }
+        if let (Some(storage), Some(chunk)) = (self.storage.as_mut(), self.chunks.get_chunk(position)) {
+            storage.save_chunk(position, chunk)?;
+            self.chunks.mark_saved(position);
+        }
+        Ok(())
+    }
+
+    // writes every changed chunk to disk
+    pub fn save(&mut self) -> io::Result<()> {
+        for position in self.chunks.unsaved_positions() {
+            self.save_chunk(position)?;
+        }
+        if let Some(storage) = self.storage.as_mut() {
+            storage.save_metadata(&self.metadata)?;
+            storage.save_pending_blocks(&self.pending_blocks)?;
+            storage.save_scheduled_updates(&self.scheduled_updates.to_saved(self.tick))?;
+            storage.save_falling_blocks(&self.falling_blocks)?;
+            storage.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn get_biomes(&self) -> &biomes::Biomes {
+        &self.biomes
+    }
+
+    pub fn get_terrain(&self) -> &Terrain {
+        &self.terrain
+    }
+
+    pub fn get_blocks(&self) -> &BlockRegistry {
+        &self.blocks
+    }
+
+    pub fn get_structures(&self) -> &Structures {
+        &self.structures
+    }
+
+    pub fn get_structures_mut(&mut self) -> &mut Structures {
+        &mut self.structures
+    }
+
+    pub fn get_chunks(&self) -> &ChunkMap {
+        &self.chunks
+    }
+
+    pub fn get_chunks_mut(&mut self) -> &mut ChunkMap {
+        &mut self.chunks
+    }
+
+    pub fn get_block(&self, position: BlockPosition) -> Option<Block> {
+        self.chunks.get_block(position)
+    }
+
+    // replaces a block, when the block type changes the old block entity is
+    // broken and the new block gets its own one
+    pub fn set_block(&mut self, position: BlockPosition, block: Block) -> Option<Block> {
+        let old_block = self.chunks.set_block(position, block)?;
+        if old_block != block {
+            self.update_heightmaps(position);
+            light::update_light(&mut self.chunks, &self.blocks, position);
+            self.schedule_fluids_around(position);
+            self.schedule_updates_around(position);
+        }
+        if old_block.id == block.id {
+            return Some(old_block);
+        }
+
+        if let Some(mut block_entity) = self.chunks.remove_block_entity(position) {
+            block_entity.on_break(self, position);
+        }
+        let kind = self.blocks.get(block.id).and_then(|definition| definition.block_entity.as_deref());
+        if let Some(mut block_entity) = kind.and_then(|kind| self.block_entity_kinds.create(kind)) {
+            block_entity.on_place(self, position);
+            // the hook may have replaced the block already
+            if self.chunks.get_block(position).is_some_and(|current| current.id == block.id) {
+                let _ = self.chunks.set_block_entity(position, block_entity);
+            }
+        }
+        Some(old_block)
+    }
+
+    // moves the world forward by one tick
+    pub fn tick(&mut self) {
+        self.tick += 1;
+        self.metadata.time.advance();
+        for position in self.fluid_ticks.take_due(self.tick, FLUID_UPDATES_PER_TICK) {
+            fluids::update_fluid(self, position);
+        }
+        for (position, reason) in self.scheduled_updates.take_due(self.tick, BLOCK_UPDATES_PER_TICK) {
+            block_updates::update_block(self, position, reason);
+        }
+        falling_blocks::update_falling_blocks(self);
+        for (position, block) in random_ticks::pick_blocks(&self.chunks, &self.blocks, &mut self.rng, RANDOM_TICKS_PER_CHUNK) {
+            random_ticks::random_tick(self, position, block);
+        }
+        weather::tick(self);
+    }
+
+    pub fn get_tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn get_time(&self) -> &WorldTime {
+        &self.metadata.time
+    }
+
+    pub fn get_time_mut(&mut self) -> &mut WorldTime {
+        &mut self.metadata.time
+    }
+
+    pub fn get_weather_settings(&self) -> &WeatherSettings {
+        &self.metadata.weather
+    }
+
+    pub fn get_weather_settings_mut(&mut self) -> &mut WeatherSettings {
+        &mut self.metadata.weather
+    }
+
+    // the weather at a position right now, it follows the climate there
+    pub fn weather_at(&self, position: BlockPosition) -> LocalWeather {
+        weather::weather_at(&self.randomness_functions, &self.metadata.weather, self.metadata.time.ticks, position)
+    }
+
+    // what falls on the position, nothing under a roof or anything else that stops a falling player
+    pub fn precipitation_at(&self, position: BlockPosition) -> Precipitation {
+        if self.highest_block(position.0, position.2, HeightmapKind::MotionBlocking).is_some_and(|top| top >= position.1) {
+            return Precipitation::None;
+        }
+        self.weather_at(position).precipitation
+    }
+
+    // the sky seen from a position, the clock's sky run through the weather hook
+    pub fn sky_at(&self, position: BlockPosition) -> Sky {
+        (self.weather_hooks.sky)(&self.weather_at(position), self.metadata.time.sky())
+    }
+
+    pub fn get_weather_hooks(&self) -> &WeatherHooks {
+        &self.weather_hooks
+    }
+
+    // mods replace how snow settles and how the weather changes the sky here
+    pub fn get_weather_hooks_mut(&mut self) -> &mut WeatherHooks {
+        &mut self.weather_hooks
+    }
+
+    pub fn get_rng_mut(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
+    }
+
+    // a changed block may let the fluids around it flow, or stop them
+    fn schedule_fluids_around(&mut self, position: BlockPosition) {
+        let offsets = std::iter::once((0, 0, 0)).chain(NEIGHBOUR_OFFSETS);
+        for offset in offsets {
+            let neighbour = (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2);
+            let Some(block) = self.chunks.get_block(neighbour) else {
+                continue;
+            };
+            if let Some(fluid) = self.blocks.fluid(block) {
+                self.fluid_ticks.schedule(neighbour, self.tick + fluid.tick_rate.max(1));
+            }
+        }
+    }
+
+    // the block gets an update for having been placed and its neighbours for
+    // the change next to them, only blocks with a "block_update" care
+    fn schedule_updates_around(&mut self, position: BlockPosition) {
+        let offsets = std::iter::once((0, 0, 0)).chain(NEIGHBOUR_OFFSETS);
+        for offset in offsets {
+            let neighbour = (position.0 + offset.0, position.1 + offset.1, position.2 + offset.2);
+            let Some(block) = self.chunks.get_block(neighbour) else {
+                continue;
+            };
+            if let Some(update) = self.blocks.get(block.id).and_then(|definition| definition.block_update.as_ref()) {
+                let reason = if offset == (0, 0, 0) { UpdateReason::Placed } else { UpdateReason::NeighbourChanged };
+                self.scheduled_updates.schedule(neighbour, self.tick + update.delay, reason);
+            }
+        }
+    }
+
+    // runs the "block_update" handler of the block at the position after the delay
+    pub fn schedule_update(&mut self, position: BlockPosition, delay: u64) {
+        self.scheduled_updates.schedule(position, self.tick + delay, UpdateReason::Requested);
+    }
+
+    pub fn get_scheduled_updates(&self) -> &ScheduledUpdates {
+        &self.scheduled_updates
+    }
+
+    pub fn add_falling_block(&mut self, falling: FallingBlock) {
+        self.falling_blocks.push(falling);
+    }
+
+    pub fn get_falling_blocks(&self) -> &[FallingBlock] {
+        &self.falling_blocks
+    }
+
+    pub fn take_falling_blocks(&mut self) -> Vec<FallingBlock> {
+        std::mem::take(&mut self.falling_blocks)
+    }
+
+    // only the column below the block is looked at again when it was the highest
+    fn update_heightmaps(&mut self, position: BlockPosition) {
+        let (x, y, z) = ChunkMap::local_position(position);
+        let Some(chunk) = self.chunks.get_chunk_mut(ChunkMap::chunk_position(position)) else {
+            return;
+        };
+        let heightmaps = match chunk.take_heightmaps() {
+            Some(mut heightmaps) => {
+                heightmaps.update(chunk, &self.blocks, x, y, z);
+                heightmaps
+            }
+            None => Heightmaps::compute(chunk, &self.blocks)
+        };
+        chunk.set_heightmaps(Some(heightmaps));
+    }
+
+    // the y of the highest block of the kind at x z, only loaded chunks are
+    // looked at so there may be more above when the column isn't fully loaded
+    pub fn highest_block(&self, x: i64, z: i64, kind: HeightmapKind) -> Option<i64> {
+        let (chunk_x, _, chunk_z) = ChunkMap::chunk_position((x, 0, z));
+        let (local_x, _, local_z) = ChunkMap::local_position((x, 0, z));
+        self.chunks.column_chunks(chunk_x, chunk_z).find_map(|chunk_y| {
+            let heightmaps = self.chunks.get_chunk((chunk_x, chunk_y, chunk_z))?.heightmaps()?;
+            let local_y = heightmaps.get(kind, local_x, local_z)?;
+            Some(ChunkMap::world_position((chunk_x, chunk_y, chunk_z), (local_x, local_y, local_z)).1)
+        })
+    }
+
+    // whether nothing above a block keeps the sky light from reaching it. the
+    // world has no top, so chunks above that aren't loaded count as open sky
+    // when they are above the terrain
+    pub fn is_sky_exposed(&self, position: BlockPosition) -> bool {
+        let (x, local_y, z) = ChunkMap::local_position(position);
+        let mut chunk_position = ChunkMap::chunk_position(position);
+        loop {
+            let Some(chunk) = self.chunks.get_chunk(chunk_position) else {
+                let bottom = chunk_position.1 * CHUNK_SIZE_Y as i64;
+                return bottom > terrain_height(position.0, position.2, &self.randomness_functions, &self.terrain);
+            };
+            let highest = match chunk.heightmaps() {
+                Some(heightmaps) => heightmaps.get(HeightmapKind::LightBlocking, x, z),
+                None => (0..CHUNK_SIZE_Y as usize).rev().find(|y| self.blocks.blocks_light(chunk.get(x, *y, z)))
+            };
+            let above = if chunk_position == ChunkMap::chunk_position(position) { Some(local_y) } else { None };
+            if highest.is_some_and(|highest| above.is_none_or(|above| highest > above)) {
+                return false;
+            }
+            chunk_position.1 += 1;
+        }
+    }
+
+    // changes a block on behalf of a player, so it can be undone. set_block is
+    // for the world itself, like generation or blocks that spread
+    pub fn edit_block(&mut self, player: PlayerId, position: BlockPosition, block: Block) -> Option<Block> {
+        self.edit_block_with_entity(player, position, block, None)
+    }
+
+    // same as edit_block, but the block gets the given block entity instead of a new one
+    pub fn edit_block_with_entity(&mut self, player: PlayerId, position: BlockPosition, block: Block, block_entity: Option<Box<dyn BlockEntity>>) -> Option<Block> {
+        let before_entity = self.chunks.get_block_entity(position).map(|block_entity| block_entity.clone_box());
+        let before = self.set_block(position, block)?;
+        if let Some(block_entity) = block_entity {
+            let _ = self.chunks.set_block_entity(position, block_entity);
+        }
+        let after_entity = self.chunks.get_block_entity(position).map(|block_entity| block_entity.clone_box());
+        self.journal.record(player, BlockChange { position, before, after: block, before_entity, after_entity });
+        Some(before)
+    }
+
+    // places a block the way a player would, its state follows the placement
+    pub fn place_block(&mut self, player: PlayerId, position: BlockPosition, id: BlockId, placement: &Placement) -> Option<Block> {
+        let block = self.blocks.placement_block(id, placement);
+        self.edit_block(player, position, block)
+    }
+
+    pub fn break_block(&mut self, player: PlayerId, position: BlockPosition) -> Option<Block> {
+        self.edit_block(player, position, AIR)
+    }
+
+    // every edit until commit_edit is undone as one
+    pub fn begin_edit(&mut self, player: PlayerId) {
+        self.journal.begin(player);
+    }
+
+    pub fn commit_edit(&mut self, player: PlayerId) {
+        self.journal.commit(player);
+    }
+
+    // returns false when there is nothing to undo. blocks in chunks that
+    // aren't loaded anymore are skipped
+    pub fn undo(&mut self, player: PlayerId) -> bool {
+        let Some(transaction) = self.journal.take_undo(player) else {
+            return false;
+        };
+        for change in transaction.changes().rev() {
+            self.restore_block(change.position, change.before, change.before_entity.as_deref());
+        }
+        self.journal.push_redo(player, transaction);
+        true
+    }
+
+    pub fn redo(&mut self, player: PlayerId) -> bool {
+        let Some(transaction) = self.journal.take_redo(player) else {
+            return false;
+        };
+        for change in transaction.changes() {
+            self.restore_block(change.position, change.after, change.after_entity.as_deref());
+        }
+        self.journal.push_redone(player, transaction);
+        true
+    }
+
+    pub fn get_journal(&self) -> &EditJournal {
+        &self.journal
+    }
+
+    pub fn get_edit_session(&mut self, player: PlayerId) -> &mut EditSession {
+        self.edit_sessions.entry(player).or_default()
+    }
+
+    // puts a block back the way it was, set_block marks the chunks for remeshing
+    fn restore_block(&mut self, position: BlockPosition, block: Block, block_entity: Option<&dyn BlockEntity>) {
+        if self.set_block(position, block).is_none() {
+            return;
+        }
+        match block_entity {
+            Some(block_entity) => {
+                let _ = self.chunks.set_block_entity(position, block_entity.clone_box());
+            }
+            None => {
+                self.chunks.remove_block_entity(position);
+            }
+        }
+    }
+
+    // whether a point in block units is inside something solid, unloaded
+    // chunks count as solid so nothing falls out of the world
+    pub fn collides_at(&self, x: f32, y: f32, z: f32) -> bool {
+        self.chunks.get_block(ChunkMap::block_at_point(x, y, z)).is_none() || self.collision_top(x, y, z).is_some()
+    }
+
+    // the top of the collision boxes of the block state a point is inside, in
+    // block units, so things stand on slabs and snow at their height. none
+    // when the point isn't inside any of them or its chunk isn't loaded
+    pub fn collision_top(&self, x: f32, y: f32, z: f32) -> Option<f32> {
+        let position = ChunkMap::block_at_point(x, y, z);
+        let block = self.chunks.get_block(position)?;
+        // blocks span -0.5 to 0.5 around their position
+        let point = [x - position.0 as f32 + 0.5, y - position.1 as f32 + 0.5, z - position.2 as f32 + 0.5];
+        self.blocks.collision_boxes(block).iter()
+            .filter(|(from, to)| (0..3).all(|axis| point[axis] >= from[axis] && point[axis] <= to[axis]))
+            .map(|(_, to)| position.1 as f32 - 0.5 + to[1])
+            .reduce(f32::max)
+    }
+
+    pub fn get_block_entity(&self, position: BlockPosition) -> Option<&dyn BlockEntity> {
+        self.chunks.get_block_entity(position)
+    }
+
+    pub fn get_block_entity_mut(&mut self, position: BlockPosition) -> Option<&mut (dyn BlockEntity + 'static)> {
+        self.chunks.get_block_entity_mut(position)
+    }
+
+    // the block entity as its concrete kind, like ContainerEntity
+    pub fn get_block_entity_as<T: BlockEntity + 'static>(&self, position: BlockPosition) -> Option<&T> {
+        self.get_block_entity(position).and_then(|block_entity| block_entity.as_any().downcast_ref::<T>())
+    }
+
+    pub fn get_block_entity_as_mut<T: BlockEntity + 'static>(&mut self, position: BlockPosition) -> Option<&mut T> {
+        self.get_block_entity_mut(position).and_then(|block_entity| block_entity.as_any_mut().downcast_mut::<T>())
+    }
+
+    pub fn get_block_entity_kinds(&self) -> &BlockEntityKinds {
+        &self.block_entity_kinds
+    }
+
+    // mods register their block entity kinds here, before any chunk is loaded
+    pub fn get_block_entity_kinds_mut(&mut self) -> &mut BlockEntityKinds {
+        &mut self.block_entity_kinds
+    }
+
+    pub fn get_random_tick_handlers(&self) -> &RandomTickHandlers {
+        &self.random_tick_handlers
+    }
+
+    // mods register the handlers their blocks name in "random_ticks" here
+    pub fn get_random_tick_handlers_mut(&mut self) -> &mut RandomTickHandlers {
+        &mut self.random_tick_handlers
+    }
+
+    pub fn get_block_update_handlers(&self) -> &BlockUpdateHandlers {
+        &self.block_update_handlers
+    }
+
+    // mods register the handlers their blocks name in "block_update" here
+    pub fn get_block_update_handlers_mut(&mut self) -> &mut BlockUpdateHandlers {
+        &mut self.block_update_handlers
+    }
+
     pub fn add_object(&mut self, object: Object) {
         self.objects.push(object);
     }
@@ -30,3 +668,9 @@ impl World {
         &self.objects
     }
 }
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
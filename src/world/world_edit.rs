@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+
+use crate::world::block_entities::BlockEntity;
+use crate::world::block_states::Orientation;
+use crate::world::chunk_map::{BlockPosition, ChunkMap, ChunkPosition};
+use crate::world::edit_journal::PlayerId;
+use crate::world::objects::chunk::{Block, BlockId, AIR};
+use crate::world::world::World;
+
+// tools for building big things at once. every operation is one transaction
+// in the edit journal, and blocks only mark their chunks as changed, so a
+// chunk is remeshed once no matter how many of its blocks an operation touched.
+// blocks in chunks that aren't loaded are left alone
+
+// a box between two corners, both corners are inside
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Region {
+    pub min: BlockPosition,
+    pub max: BlockPosition
+}
+impl Region {
+    pub fn new(first: BlockPosition, second: BlockPosition) -> Self {
+        Self {
+            min: (first.0.min(second.0), first.1.min(second.1), first.2.min(second.2)),
+            max: (first.0.max(second.0), first.1.max(second.1), first.2.max(second.2))
+        }
+    }
+
+    pub fn size(&self) -> (i64, i64, i64) {
+        (self.max.0 - self.min.0 + 1, self.max.1 - self.min.1 + 1, self.max.2 - self.min.2 + 1)
+    }
+
+    // saturates instead of overflowing for selections across the whole world
+    pub fn volume(&self) -> u64 {
+        let x = self.max.0.abs_diff(self.min.0).saturating_add(1);
+        let y = self.max.1.abs_diff(self.min.1).saturating_add(1);
+        let z = self.max.2.abs_diff(self.min.2).saturating_add(1);
+        x.saturating_mul(y).saturating_mul(z)
+    }
+
+    pub fn contains(&self, position: BlockPosition) -> bool {
+        position.0 >= self.min.0 && position.1 >= self.min.1 && position.2 >= self.min.2 &&
+        position.0 <= self.max.0 && position.1 <= self.max.1 && position.2 <= self.max.2
+    }
+
+    // whether the position is on the outside of the box
+    pub fn is_wall(&self, position: BlockPosition) -> bool {
+        position.0 == self.min.0 || position.1 == self.min.1 || position.2 == self.min.2 ||
+        position.0 == self.max.0 || position.1 == self.max.1 || position.2 == self.max.2
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = BlockPosition> {
+        let (min, max) = (self.min, self.max);
+        (min.0..=max.0).flat_map(move |x| {
+            (min.1..=max.1).flat_map(move |y| (min.2..=max.2).map(move |z| (x, y, z)))
+        })
+    }
+
+    pub fn offset(&self, offset: (i64, i64, i64)) -> Self {
+        Self {
+            min: (self.min.0 + offset.0, self.min.1 + offset.1, self.min.2 + offset.2),
+            max: (self.max.0 + offset.0, self.max.1 + offset.1, self.max.2 + offset.2)
+        }
+    }
+
+    pub fn chunks(&self) -> HashSet<ChunkPosition> {
+        let min = ChunkMap::chunk_position(self.min);
+        let max = ChunkMap::chunk_position(self.max);
+        Region::new(min, max).positions().collect()
+    }
+}
+
+// the two corners a player picked, the region exists once both are set
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Selection {
+    pub first: Option<BlockPosition>,
+    pub second: Option<BlockPosition>
+}
+impl Selection {
+    pub fn region(&self) -> Option<Region> {
+        Some(Region::new(self.first?, self.second?))
+    }
+}
+
+#[derive(Clone)]
+pub struct ClipboardBlock {
+    // relative to where the player stood when copying
+    pub offset: (i64, i64, i64),
+    pub block: Block,
+    pub block_entity: Option<Box<dyn BlockEntity>>
+}
+
+// copied blocks, air included so pasting can clear what was there
+#[derive(Clone, Default)]
+pub struct Clipboard {
+    pub blocks: Vec<ClipboardBlock>
+}
+impl Clipboard {
+    // the clipboard turned around the copy origin, block states turn with it
+    pub fn transformed(&self, world: &World, transform: &Transform) -> Self {
+        let orientation = transform.orientation();
+        let blocks = self.blocks.iter()
+            .map(|clipboard_block| ClipboardBlock {
+                offset: orientation.apply_offset(clipboard_block.offset),
+                block: world.get_blocks().transform_block(clipboard_block.block, &orientation),
+                block_entity: clipboard_block.block_entity.clone()
+            })
+            .collect();
+        Self { blocks }
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+// how a paste is turned, mirroring happens before the rotation
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Transform {
+    // counter clockwise around the y axis, seen from above
+    pub quarter_turns: i32,
+    pub mirror_x: bool,
+    pub mirror_z: bool
+}
+impl Transform {
+    pub fn orientation(&self) -> Orientation {
+        let mut orientation = Orientation::IDENTITY;
+        if self.mirror_x {
+            orientation = orientation.then(Orientation::mirror(0));
+        }
+        if self.mirror_z {
+            orientation = orientation.then(Orientation::mirror(2));
+        }
+        orientation.then(Orientation::quarter_turns(1, self.quarter_turns))
+    }
+}
+
+// what a player has selected and copied
+#[derive(Clone, Default)]
+pub struct EditSession {
+    pub selection: Selection,
+    pub clipboard: Option<Clipboard>
+}
+
+// all the operations return how many blocks they changed
+
+pub fn fill(world: &mut World, player: PlayerId, region: Region, block: Block) -> usize {
+    edit(world, player, region.positions().map(|position| (position, block)))
+}
+
+// walls of the block with air inside
+pub fn hollow(world: &mut World, player: PlayerId, region: Region, block: Block) -> usize {
+    edit(world, player, region.positions().map(|position| {
+        (position, if region.is_wall(position) { block } else { AIR })
+    }))
+}
+
+// replaces every block of a type, whatever its state
+pub fn replace(world: &mut World, player: PlayerId, region: Region, from: BlockId, to: Block) -> usize {
+    let positions: Vec<BlockPosition> = region.positions()
+        .filter(|position| world.get_block(*position).is_some_and(|block| block.id == from))
+        .collect();
+    edit(world, player, positions.into_iter().map(|position| (position, to)))
+}
+
+pub fn copy(world: &World, region: Region, origin: BlockPosition) -> Clipboard {
+    let blocks = region.positions()
+        .filter_map(|position| {
+            let block = world.get_block(position)?;
+            Some(ClipboardBlock {
+                offset: (position.0 - origin.0, position.1 - origin.1, position.2 - origin.2),
+                block,
+                block_entity: world.get_block_entity(position).map(|block_entity| block_entity.clone_box())
+            })
+        })
+        .collect();
+    Clipboard { blocks }
+}
+
+pub fn paste(world: &mut World, player: PlayerId, clipboard: &Clipboard, position: BlockPosition, transform: &Transform, include_air: bool) -> usize {
+    let clipboard = clipboard.transformed(world, transform);
+    world.begin_edit(player);
+    let mut changed = 0;
+    for clipboard_block in clipboard.blocks {
+        if !include_air && clipboard_block.block.is_air() {
+            continue;
+        }
+        let target = (position.0 + clipboard_block.offset.0, position.1 + clipboard_block.offset.1, position.2 + clipboard_block.offset.2);
+        let old_block = world.edit_block_with_entity(player, target, clipboard_block.block, clipboard_block.block_entity);
+        if old_block.is_some_and(|old_block| old_block != clipboard_block.block) {
+            changed += 1;
+        }
+    }
+    world.commit_edit(player);
+    changed
+}
+
+// moves the blocks and leaves air behind, the player's selection moves along
+pub fn move_region(world: &mut World, player: PlayerId, region: Region, offset: (i64, i64, i64)) -> usize {
+    let clipboard = copy(world, region, region.min);
+    world.begin_edit(player);
+    let cleared = fill(world, player, region, AIR);
+    let target = (region.min.0 + offset.0, region.min.1 + offset.1, region.min.2 + offset.2);
+    let pasted = paste(world, player, &clipboard, target, &Transform::default(), true);
+    world.commit_edit(player);
+
+    let session = world.get_edit_session(player);
+    if session.selection.region() == Some(region) {
+        let moved = region.offset(offset);
+        session.selection = Selection { first: Some(moved.min), second: Some(moved.max) };
+    }
+    cleared.max(pasted)
+}
+
+fn edit(world: &mut World, player: PlayerId, blocks: impl Iterator<Item = (BlockPosition, Block)>) -> usize {
+    world.begin_edit(player);
+    let mut changed = 0;
+    for (position, block) in blocks {
+        if world.edit_block(player, position, block).is_some_and(|old_block| old_block != block) {
+            changed += 1;
+        }
+    }
+    world.commit_edit(player);
+    changed
+}